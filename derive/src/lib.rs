@@ -0,0 +1,742 @@
+//! Derive macros for the traits of the `dyn_ord` crate.
+//!
+//! Types that implement `PartialEq` and `PartialOrd` get `DynEq` and
+//! `DynOrd` from the blanket impls in `dyn_ord`, so these macros are meant
+//! for types that don't (or can't) implement the standard traits, for
+//! example because some of their fields are trait objects. The derived
+//! impls compare values field by field, using the `DynEq` and `DynOrd`
+//! impls of each field.
+//!
+//! This crate is re-exported by `dyn_ord` when its `derive` feature is
+//! enabled, and it should not be depended upon directly.
+
+extern crate proc_macro;
+
+use proc_macro::{Delimiter, Spacing, TokenStream, TokenTree};
+use std::fmt::Write;
+
+/// Derives `DynEq` by comparing values field by field.
+///
+/// Two values are equal if they are of the same type, they are the same
+/// variant (in the case of enums), and all of their corresponding fields
+/// are equal according to `DynEq`. Every field must therefore implement
+/// `DynEq`, and the type must not have lifetime parameters, since
+/// `DynEq` requires `'static`.
+///
+/// The type must not implement `PartialEq`, otherwise the derived impl
+/// conflicts with the blanket impl in `dyn_ord`.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// #[derive(DynEq)]
+/// struct Entry {
+///     key: String,
+///     value: Box<dyn DynEq>,
+/// }
+///
+/// let x: &dyn DynEq = &Entry { key: "answer".into(), value: Box::new(42) };
+/// let y: &dyn DynEq = &Entry { key: "answer".into(), value: Box::new(42) };
+/// let z: &dyn DynEq = &Entry { key: "answer".into(), value: Box::new("42") };
+///
+/// assert!(*x == *y);
+/// assert!(*x != *z);
+/// ```
+#[proc_macro_derive(DynEq)]
+pub fn derive_dyn_eq(input: TokenStream) -> TokenStream {
+    match Input::parse(input) {
+        Ok(input) => input.dyn_eq_impl(),
+        Err(message) => compile_error(&message),
+    }
+}
+
+/// Derives `DynOrd` by comparing values lexicographically, field by field.
+///
+/// Values of the same struct type are ordered by their first field that
+/// does not compare equal according to `DynOrd`. Values of the same enum
+/// type are ordered by the declaration order of their variants first,
+/// then by their fields. If any pair of corresponding fields is
+/// incomparable, so are the values. Every field must implement `DynOrd`.
+///
+/// Since `DynOrd` requires `DynEq`, this also derives `DynEq`. If `DynEq`
+/// is implemented or derived separately, add the `#[dyn_ord(skip_eq)]`
+/// attribute to the type to prevent conflicting impls.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// #[derive(DynOrd)]
+/// enum Priority {
+///     Low,
+///     High(Box<dyn DynOrd>),
+/// }
+///
+/// let low: &dyn DynOrd = &Priority::Low;
+/// let high_1: &dyn DynOrd = &Priority::High(Box::new(1));
+/// let high_2: &dyn DynOrd = &Priority::High(Box::new(2));
+/// let high_x: &dyn DynOrd = &Priority::High(Box::new('x'));
+///
+/// assert!(*low < *high_1);
+/// assert!(*high_1 < *high_2);
+/// assert!(*high_2 == *high_2);
+/// assert_eq!(high_1.partial_cmp(high_x), None);
+/// ```
+///
+/// ```
+/// # use dyn_ord::{DynEq, DynOrd};
+/// #[derive(DynEq, DynOrd)]
+/// #[dyn_ord(skip_eq)]
+/// struct Version(Box<dyn DynOrd>, Box<dyn DynOrd>);
+///
+/// let x: &dyn DynOrd = &Version(Box::new(1), Box::new(2));
+/// let y: &dyn DynOrd = &Version(Box::new(1), Box::new(3));
+///
+/// assert!(*x < *y);
+/// ```
+#[proc_macro_derive(DynOrd, attributes(dyn_ord))]
+pub fn derive_dyn_ord(input: TokenStream) -> TokenStream {
+    match Input::parse(input) {
+        Ok(input) => {
+            let mut output = input.dyn_ord_impl();
+
+            if !input.skip_eq {
+                output.extend(input.dyn_eq_impl());
+            }
+
+            output
+        }
+        Err(message) => compile_error(&message),
+    }
+}
+
+/// The parsed form of a struct or enum definition.
+struct Input {
+    name: String,
+    /// Whether `#[dyn_ord(skip_eq)]` was specified.
+    skip_eq: bool,
+    params: Vec<Param>,
+    where_clause: String,
+    data: Data,
+}
+
+/// A generic parameter of the type being derived for.
+struct Param {
+    /// The declaration, including bounds but without the default value.
+    decl: String,
+    /// The parameter as it appears in a generic argument list.
+    arg: String,
+    kind: ParamKind,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ParamKind {
+    Lifetime,
+    Type,
+    Const,
+}
+
+enum Data {
+    Struct(Fields),
+    Enum(Vec<Variant>),
+}
+
+struct Variant {
+    name: String,
+    fields: Fields,
+}
+
+enum Fields {
+    /// Field names and types.
+    Named(Vec<(String, String)>),
+    /// Field types.
+    Unnamed(Vec<String>),
+    Unit,
+}
+
+impl Input {
+    fn parse(input: TokenStream) -> Result<Self, String> {
+        let tokens: Vec<TokenTree> = input.into_iter().collect();
+        let mut skip_eq = false;
+
+        for flag in helper_attribute_flags(&tokens)? {
+            match flag.as_str() {
+                "skip_eq" => skip_eq = true,
+                _ => return Err(format!("unknown `dyn_ord` attribute: `{}`", flag)),
+            }
+        }
+
+        let mut pos = skip_attributes_and_visibility(&tokens, 0);
+
+        let kind = match tokens.get(pos) {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err("expected a struct or an enum".into()),
+        };
+        pos += 1;
+
+        let name = match tokens.get(pos) {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err("expected the name of the type".into()),
+        };
+        pos += 1;
+
+        let mut params = Vec::new();
+
+        if is_punct(tokens.get(pos), '<') {
+            let end = find_closing_angle(&tokens, pos + 1)
+                .ok_or("unterminated generic parameter list")?;
+
+            for param in split_top_level_commas(&tokens[pos + 1..end]) {
+                params.push(Param::parse(param)?);
+            }
+
+            pos = end + 1;
+        }
+
+        let mut where_clause = Vec::new();
+        let mut body = None;
+
+        // Everything up to the body or the final semicolon is either the
+        // tuple struct fields or the where clause.
+        while let Some(token) = tokens.get(pos) {
+            match token {
+                TokenTree::Group(group) if group.delimiter() == Delimiter::Brace => {
+                    body = Some(group.clone());
+                    break;
+                }
+                TokenTree::Group(group)
+                    if group.delimiter() == Delimiter::Parenthesis && where_clause.is_empty() =>
+                {
+                    body = Some(group.clone());
+                }
+                TokenTree::Punct(punct) if punct.as_char() == ';' => break,
+                TokenTree::Ident(ident) if ident.to_string() == "where" && where_clause.is_empty() => {}
+                _ => where_clause.push(token.clone()),
+            }
+            pos += 1;
+        }
+
+        let data = match kind.as_str() {
+            "struct" => Data::Struct(match body {
+                None => Fields::Unit,
+                Some(group) => Fields::parse(group.delimiter(), group.stream())?,
+            }),
+            "enum" => {
+                let body = body.ok_or("expected the variants of the enum")?;
+                let tokens: Vec<TokenTree> = body.stream().into_iter().collect();
+                let variants = split_top_level_commas(&tokens)
+                    .into_iter()
+                    .map(Variant::parse)
+                    .collect::<Result<_, _>>()?;
+
+                Data::Enum(variants)
+            }
+            "union" => return Err("unions are not supported".into()),
+            _ => return Err("expected a struct or an enum".into()),
+        };
+
+        let mut where_clause = tokens_to_string(&where_clause);
+        if !where_clause.is_empty() && !where_clause.trim_end().ends_with(',') {
+            where_clause.push(',');
+        }
+
+        Ok(Input { name, skip_eq, params, where_clause, data })
+    }
+
+    /// Returns an error if the type can not be `'static`.
+    fn check_static(&self, trait_name: &str) -> Result<(), String> {
+        if self.params.iter().any(|param| param.kind == ParamKind::Lifetime) {
+            Err(format!(
+                "`{}` can not be derived for types with lifetime parameters, as it requires `'static`",
+                trait_name,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the header of the impl of `trait_path`, up to and including
+    /// the opening brace, bounding the type of every field by `field_bound`.
+    fn impl_header(&self, trait_path: &str, field_bound: &str) -> String {
+        let mut header = String::from("#[automatically_derived] impl");
+
+        if !self.params.is_empty() {
+            let decls: Vec<&str> = self.params.iter().map(|param| param.decl.as_str()).collect();
+            write!(header, "<{}>", decls.join(", ")).unwrap();
+        }
+
+        write!(header, " {} for {}", trait_path, self.name).unwrap();
+
+        if !self.params.is_empty() {
+            let args: Vec<&str> = self.params.iter().map(|param| param.arg.as_str()).collect();
+            write!(header, "<{}>", args.join(", ")).unwrap();
+        }
+
+        write!(header, " where {}", self.where_clause).unwrap();
+
+        for param in &self.params {
+            if param.kind == ParamKind::Type {
+                write!(header, " {}: 'static,", param.arg).unwrap();
+            }
+        }
+
+        for ty in self.field_types() {
+            write!(header, " {}: {},", ty, field_bound).unwrap();
+        }
+
+        header.push('{');
+        header
+    }
+
+    fn field_types(&self) -> Vec<&str> {
+        match self.data {
+            Data::Struct(ref fields) => fields.types(),
+            Data::Enum(ref variants) => variants.iter().flat_map(|v| v.fields.types()).collect(),
+        }
+    }
+
+    fn dyn_eq_impl(&self) -> TokenStream {
+        if let Err(message) = self.check_static("DynEq") {
+            return compile_error(&message);
+        }
+
+        let mut code = self.impl_header("::dyn_ord::DynEq", "::dyn_ord::DynEq");
+
+        code.push_str(
+            "
+            fn as_any(&self) -> &dyn ::core::any::Any {
+                self
+            }
+
+            fn as_any_mut(&mut self) -> &mut dyn ::core::any::Any {
+                self
+            }
+
+            fn dyn_type_name(&self) -> &'static str {
+                ::core::any::type_name::<Self>()
+            }
+
+            fn as_dyn_eq(&self) -> &dyn ::dyn_ord::DynEq {
+                self
+            }
+
+            fn dyn_eq(&self, other: &dyn ::dyn_ord::DynEq) -> bool {
+                ::dyn_ord::DynEq::dyn_eq_typed(self, other).unwrap_or(false)
+            }
+
+            fn dyn_eq_typed(&self, other: &dyn ::dyn_ord::DynEq) -> ::core::option::Option<bool> {
+                let other = ::dyn_ord::DynEq::as_any(other).downcast_ref::<Self>()?;
+                ::core::option::Option::Some(
+            ",
+        );
+
+        code.push_str(&self.eq_expr());
+        code.push_str(") } }");
+
+        code.parse().unwrap()
+    }
+
+    fn dyn_ord_impl(&self) -> TokenStream {
+        if let Err(message) = self.check_static("DynOrd") {
+            return compile_error(&message);
+        }
+
+        let mut code = self.impl_header("::dyn_ord::DynOrd", "::dyn_ord::DynOrd");
+
+        code.push_str(
+            "
+            fn as_dyn_ord(&self) -> &dyn ::dyn_ord::DynOrd {
+                self
+            }
+
+            fn dyn_ord(
+                &self,
+                other: &dyn ::dyn_ord::DynOrd,
+            ) -> ::core::option::Option<::core::cmp::Ordering> {
+                let other = ::dyn_ord::DynEq::as_any(other).downcast_ref::<Self>()?;
+            ",
+        );
+
+        code.push_str(&self.ord_expr());
+        code.push_str(" } }");
+
+        code.parse().unwrap()
+    }
+
+    /// Returns an expression comparing `self` and `other` of type `&Self`
+    /// lexicographically, field by field.
+    fn ord_expr(&self) -> String {
+        fn all_fields_cmp(lhs: &[String], rhs: &[String]) -> String {
+            let mut block = String::from("{");
+
+            for (lhs, rhs) in lhs.iter().zip(rhs) {
+                write!(
+                    block,
+                    "match ::dyn_ord::DynOrd::dyn_ord({}, ::dyn_ord::DynOrd::as_dyn_ord({})) {{
+                        ::core::option::Option::Some(::core::cmp::Ordering::Equal) => {{}}
+                        cmp => return cmp,
+                    }}",
+                    lhs, rhs,
+                )
+                .unwrap();
+            }
+
+            block.push_str("::core::option::Option::Some(::core::cmp::Ordering::Equal) }");
+            block
+        }
+
+        match self.data {
+            Data::Struct(ref fields) => {
+                let lhs = fields.accessors("&self.");
+                let rhs = fields.accessors("&other.");
+                all_fields_cmp(&lhs, &rhs)
+            }
+            Data::Enum(ref variants) => {
+                let mut indices = String::new();
+                let mut arms = String::new();
+
+                for (index, variant) in variants.iter().enumerate() {
+                    let (lhs_pattern, lhs) = variant.pattern("__self_");
+                    let (rhs_pattern, rhs) = variant.pattern("__other_");
+
+                    write!(indices, "{} => {},", variant.wildcard_pattern(), index).unwrap();
+                    write!(
+                        arms,
+                        "({}, {}) => {},",
+                        lhs_pattern,
+                        rhs_pattern,
+                        all_fields_cmp(&lhs, &rhs),
+                    )
+                    .unwrap();
+                }
+
+                format!(
+                    "let index = |value: &Self| -> usize {{ match *value {{ {} }} }};
+                    match (self, other) {{
+                        {}
+                        #[allow(unreachable_patterns)]
+                        _ => ::core::option::Option::Some(
+                            ::core::cmp::Ord::cmp(&index(self), &index(other))
+                        ),
+                    }}",
+                    indices, arms,
+                )
+            }
+        }
+    }
+
+    /// Returns an expression comparing `self` and `other` of type `&Self`
+    /// field by field.
+    fn eq_expr(&self) -> String {
+        fn all_fields_eq(lhs: &[String], rhs: &[String]) -> String {
+            let comparisons: Vec<String> = lhs
+                .iter()
+                .zip(rhs)
+                .map(|(lhs, rhs)| {
+                    format!(
+                        "::dyn_ord::DynEq::dyn_eq({}, ::dyn_ord::DynEq::as_dyn_eq({}))",
+                        lhs, rhs,
+                    )
+                })
+                .collect();
+
+            if comparisons.is_empty() {
+                String::from("true")
+            } else {
+                comparisons.join(" && ")
+            }
+        }
+
+        match self.data {
+            Data::Struct(ref fields) => {
+                let lhs = fields.accessors("&self.");
+                let rhs = fields.accessors("&other.");
+                all_fields_eq(&lhs, &rhs)
+            }
+            Data::Enum(ref variants) => {
+                let mut arms = String::new();
+
+                for variant in variants {
+                    let (lhs_pattern, lhs) = variant.pattern("__self_");
+                    let (rhs_pattern, rhs) = variant.pattern("__other_");
+
+                    write!(
+                        arms,
+                        "({}, {}) => {},",
+                        lhs_pattern,
+                        rhs_pattern,
+                        all_fields_eq(&lhs, &rhs),
+                    )
+                    .unwrap();
+                }
+
+                format!(
+                    "match (self, other) {{ {} #[allow(unreachable_patterns)] _ => false, }}",
+                    arms,
+                )
+            }
+        }
+    }
+}
+
+impl Param {
+    fn parse(tokens: &[TokenTree]) -> Result<Self, String> {
+        // strip the default value, if any
+        let end = tokens
+            .iter()
+            .position(|token| is_punct(Some(token), '='))
+            .unwrap_or(tokens.len());
+        let decl = tokens_to_string(&tokens[..end]);
+
+        match tokens {
+            [TokenTree::Punct(quote), TokenTree::Ident(name), ..] if quote.as_char() == '\'' => Ok(Param {
+                decl,
+                arg: format!("'{}", name),
+                kind: ParamKind::Lifetime,
+            }),
+            [TokenTree::Ident(keyword), TokenTree::Ident(name), ..] if keyword.to_string() == "const" => Ok(Param {
+                decl,
+                arg: name.to_string(),
+                kind: ParamKind::Const,
+            }),
+            [TokenTree::Ident(name), ..] => Ok(Param {
+                decl,
+                arg: name.to_string(),
+                kind: ParamKind::Type,
+            }),
+            _ => Err("unexpected token in generic parameter list".into()),
+        }
+    }
+}
+
+impl Variant {
+    fn parse(tokens: &[TokenTree]) -> Result<Self, String> {
+        let pos = skip_attributes_and_visibility(tokens, 0);
+
+        let name = match tokens.get(pos) {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _ => return Err("expected the name of an enum variant".into()),
+        };
+
+        let fields = match tokens.get(pos + 1) {
+            Some(TokenTree::Group(group)) => Fields::parse(group.delimiter(), group.stream())?,
+            _ => Fields::Unit,
+        };
+
+        Ok(Variant { name, fields })
+    }
+
+    /// Returns a pattern matching this variant, binding its fields to
+    /// variables with the given prefix, and the names of those variables.
+    fn pattern(&self, prefix: &str) -> (String, Vec<String>) {
+        match self.fields {
+            Fields::Named(ref fields) => {
+                let bindings: Vec<String> = (0..fields.len()).map(|i| format!("{}{}", prefix, i)).collect();
+                let fields: Vec<String> = fields
+                    .iter()
+                    .zip(&bindings)
+                    .map(|((name, _), binding)| format!("{}: {}", name, binding))
+                    .collect();
+
+                (format!("Self::{} {{ {} }}", self.name, fields.join(", ")), bindings)
+            }
+            Fields::Unnamed(ref types) => {
+                let bindings: Vec<String> = (0..types.len()).map(|i| format!("{}{}", prefix, i)).collect();
+
+                (format!("Self::{}({})", self.name, bindings.join(", ")), bindings)
+            }
+            Fields::Unit => (format!("Self::{}", self.name), Vec::new()),
+        }
+    }
+}
+
+impl Variant {
+    /// Returns a pattern matching this variant regardless of its fields.
+    fn wildcard_pattern(&self) -> String {
+        match self.fields {
+            Fields::Named(_) => format!("Self::{} {{ .. }}", self.name),
+            Fields::Unnamed(_) => format!("Self::{}(..)", self.name),
+            Fields::Unit => format!("Self::{}", self.name),
+        }
+    }
+}
+
+impl Fields {
+    fn parse(delimiter: Delimiter, stream: TokenStream) -> Result<Self, String> {
+        let tokens: Vec<TokenTree> = stream.into_iter().collect();
+        let fields = split_top_level_commas(&tokens);
+
+        match delimiter {
+            Delimiter::Brace => {
+                let mut named = Vec::with_capacity(fields.len());
+
+                for field in fields {
+                    let pos = skip_attributes_and_visibility(field, 0);
+
+                    match (field.get(pos), field.get(pos + 1)) {
+                        (Some(TokenTree::Ident(name)), Some(colon)) if is_punct(Some(colon), ':') => {
+                            named.push((name.to_string(), tokens_to_string(&field[pos + 2..])));
+                        }
+                        _ => return Err("expected a named field".into()),
+                    }
+                }
+
+                Ok(Fields::Named(named))
+            }
+            Delimiter::Parenthesis => Ok(Fields::Unnamed(
+                fields
+                    .into_iter()
+                    .map(|field| tokens_to_string(&field[skip_attributes_and_visibility(field, 0)..]))
+                    .collect(),
+            )),
+            _ => Err("unexpected delimiter around fields".into()),
+        }
+    }
+
+    fn types(&self) -> Vec<&str> {
+        match *self {
+            Fields::Named(ref fields) => fields.iter().map(|(_, ty)| ty.as_str()).collect(),
+            Fields::Unnamed(ref types) => types.iter().map(String::as_str).collect(),
+            Fields::Unit => Vec::new(),
+        }
+    }
+
+    /// Returns expressions accessing each field through `prefix`.
+    fn accessors(&self, prefix: &str) -> Vec<String> {
+        match *self {
+            Fields::Named(ref fields) => fields.iter().map(|(name, _)| format!("{}{}", prefix, name)).collect(),
+            Fields::Unnamed(ref types) => (0..types.len()).map(|i| format!("{}{}", prefix, i)).collect(),
+            Fields::Unit => Vec::new(),
+        }
+    }
+}
+
+/// Returns the flags specified in `#[dyn_ord(...)]` outer attributes.
+fn helper_attribute_flags(tokens: &[TokenTree]) -> Result<Vec<String>, String> {
+    let mut flags = Vec::new();
+
+    for pair in tokens.windows(2) {
+        let group = match pair {
+            [TokenTree::Punct(punct), TokenTree::Group(group)]
+                if punct.as_char() == '#' && group.delimiter() == Delimiter::Bracket =>
+            {
+                group
+            }
+            _ => continue,
+        };
+
+        let attr: Vec<TokenTree> = group.stream().into_iter().collect();
+
+        match attr.as_slice() {
+            [TokenTree::Ident(name), TokenTree::Group(args)] if name.to_string() == "dyn_ord" => {
+                let args: Vec<TokenTree> = args.stream().into_iter().collect();
+
+                for arg in split_top_level_commas(&args) {
+                    match arg {
+                        [TokenTree::Ident(flag)] => flags.push(flag.to_string()),
+                        _ => return Err("expected `#[dyn_ord(skip_eq)]`".into()),
+                    }
+                }
+            }
+            [TokenTree::Ident(name), ..] if name.to_string() == "dyn_ord" => {
+                return Err("expected `#[dyn_ord(skip_eq)]`".into());
+            }
+            _ => {}
+        }
+    }
+
+    Ok(flags)
+}
+
+/// Returns the position of the first token after the outer attributes
+/// and the visibility qualifier starting at `pos`.
+fn skip_attributes_and_visibility(tokens: &[TokenTree], mut pos: usize) -> usize {
+    loop {
+        match (tokens.get(pos), tokens.get(pos + 1)) {
+            (Some(TokenTree::Punct(punct)), Some(TokenTree::Group(group)))
+                if punct.as_char() == '#' && group.delimiter() == Delimiter::Bracket =>
+            {
+                pos += 2;
+            }
+            (Some(TokenTree::Ident(ident)), next) if ident.to_string() == "pub" => {
+                pos += 1;
+
+                if let Some(TokenTree::Group(group)) = next {
+                    if group.delimiter() == Delimiter::Parenthesis {
+                        pos += 1;
+                    }
+                }
+            }
+            _ => return pos,
+        }
+    }
+}
+
+/// Returns the position of the `>` closing the angle brackets opened
+/// right before `pos`.
+fn find_closing_angle(tokens: &[TokenTree], pos: usize) -> Option<usize> {
+    let mut depth = 0_usize;
+
+    for (i, token) in tokens.iter().enumerate().skip(pos) {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' && !is_arrow(tokens, i) => {
+                if depth == 0 {
+                    return Some(i);
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Splits tokens at commas that are not nested in angle brackets,
+/// ignoring a trailing comma.
+fn split_top_level_commas(tokens: &[TokenTree]) -> Vec<&[TokenTree]> {
+    let mut parts = Vec::new();
+    let mut depth = 0_usize;
+    let mut start = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == '<' => depth += 1,
+            TokenTree::Punct(punct) if punct.as_char() == '>' && !is_arrow(tokens, i) => {
+                depth = depth.saturating_sub(1);
+            }
+            TokenTree::Punct(punct) if punct.as_char() == ',' && depth == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if start < tokens.len() {
+        parts.push(&tokens[start..]);
+    }
+
+    parts
+}
+
+/// Returns `true` if the `>` at position `i` is part of a `->`.
+fn is_arrow(tokens: &[TokenTree], i: usize) -> bool {
+    match i.checked_sub(1).and_then(|j| tokens.get(j)) {
+        Some(TokenTree::Punct(punct)) => punct.as_char() == '-' && punct.spacing() == Spacing::Joint,
+        _ => false,
+    }
+}
+
+fn is_punct(token: Option<&TokenTree>, ch: char) -> bool {
+    match token {
+        Some(TokenTree::Punct(punct)) => punct.as_char() == ch,
+        _ => false,
+    }
+}
+
+fn tokens_to_string(tokens: &[TokenTree]) -> String {
+    tokens.iter().cloned().collect::<TokenStream>().to_string()
+}
+
+fn compile_error(message: &str) -> TokenStream {
+    format!("::core::compile_error!({:?});", message).parse().unwrap()
+}
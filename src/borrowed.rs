@@ -0,0 +1,217 @@
+//! Dynamically-typed comparison of values that are not `'static`.
+//!
+//! `DynEq` relies on `Any` for identifying the underlying type of a trait
+//! object, which is only implemented for `'static` types. The traits in
+//! this module identify types via [`BorrowedType`] instead, which maps a
+//! type containing borrows of lifetime `'a` to the same type with `'a`
+//! replaced by `'static`. Since the lifetime parameter of a trait object
+//! is invariant, two `dyn BorrowedDynEq<'a>` values always agree on `'a`,
+//! so it is enough to compare the `TypeId`s of their `'static` versions.
+
+use core::any::TypeId;
+use core::cmp::Ordering;
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// A type that may contain borrows of lifetime `'a`, along with its
+/// `'static` counterpart, which is used for identifying it at runtime.
+///
+/// This is implemented for primitives, strings, and references, boxes,
+/// slices, arrays, vectors and options of `BorrowedType`s.
+///
+/// # Safety
+///
+/// `Static` must be the same type as `Self`, except that every occurrence
+/// of the lifetime `'a` must be replaced by `'static`. `Self` must not
+/// contain any other lifetimes (apart from `'static`). In particular,
+/// distinct types must not have the same `Static` type, since values are
+/// cast to their concrete type only based on the `TypeId` of `Static`.
+///
+/// ```
+/// # use dyn_ord::{BorrowedType, BorrowedDynEq};
+/// #[derive(PartialEq)]
+/// struct Token<'a> {
+///     text: &'a str,
+/// }
+///
+/// unsafe impl<'a> BorrowedType<'a> for Token<'a> {
+///     type Static = Token<'static>;
+/// }
+///
+/// let text = String::from("qux");
+/// let x: &dyn BorrowedDynEq<'_> = &Token { text: &text };
+///
+/// assert!(*x == *x);
+/// assert!(*x != *(&text.as_str() as &dyn BorrowedDynEq<'_>));
+/// ```
+pub unsafe trait BorrowedType<'a>: 'a {
+    /// This type, with the lifetime `'a` replaced by `'static`.
+    type Static: ?Sized + 'static;
+}
+
+macro_rules! impl_borrowed_type_static {
+    ($($ty:ty),*) => {$(
+        unsafe impl<'a> BorrowedType<'a> for $ty {
+            type Static = $ty;
+        }
+    )*}
+}
+
+impl_borrowed_type_static! {
+    (), bool, char, str, String,
+    i8, i16, i32, i64, i128, isize,
+    u8, u16, u32, u64, u128, usize,
+    f32, f64
+}
+
+unsafe impl<'a, T: ?Sized + BorrowedType<'a>> BorrowedType<'a> for &'a T {
+    type Static = &'static T::Static;
+}
+
+unsafe impl<'a, T: ?Sized + BorrowedType<'a>> BorrowedType<'a> for Box<T> {
+    type Static = Box<T::Static>;
+}
+
+unsafe impl<'a, T: BorrowedType<'a>> BorrowedType<'a> for [T]
+where
+    T::Static: Sized,
+{
+    type Static = [T::Static];
+}
+
+unsafe impl<'a, T: BorrowedType<'a>, const N: usize> BorrowedType<'a> for [T; N]
+where
+    T::Static: Sized,
+{
+    type Static = [T::Static; N];
+}
+
+unsafe impl<'a, T: BorrowedType<'a>> BorrowedType<'a> for Vec<T>
+where
+    T::Static: Sized,
+{
+    type Static = Vec<T::Static>;
+}
+
+unsafe impl<'a, T: BorrowedType<'a>> BorrowedType<'a> for Option<T>
+where
+    T::Static: Sized,
+{
+    type Static = Option<T::Static>;
+}
+
+mod private {
+    use core::any::TypeId;
+    use super::BorrowedType;
+
+    /// Provides the runtime type identity of `BorrowedDynEq` trait
+    /// objects. This is sealed, so that the identity can't be forged by
+    /// implementing `BorrowedDynEq` manually.
+    pub trait BorrowedTypeId<'a> {
+        fn borrowed_type_id(&self) -> TypeId;
+    }
+
+    impl<'a, T: BorrowedType<'a>> BorrowedTypeId<'a> for T {
+        fn borrowed_type_id(&self) -> TypeId {
+            TypeId::of::<T::Static>()
+        }
+    }
+}
+
+/// A trait for comparing dynamically-typed values for equality, which may
+/// contain borrows of lifetime `'a`.
+///
+/// This is the non-`'static` counterpart of `DynEq`. It is implemented for
+/// every type that is `PartialEq` and [`BorrowedType`]. As with `DynEq`,
+/// values of different types are considered not equal.
+///
+/// ```
+/// # use dyn_ord::BorrowedDynEq;
+/// let owned = String::from("qux");
+/// let borrowed: &str = &owned;
+///
+/// let x: &dyn BorrowedDynEq<'_> = &borrowed;
+/// let y: &dyn BorrowedDynEq<'_> = &"qux";
+/// let z: &dyn BorrowedDynEq<'_> = &owned;
+///
+/// assert!(*x == *y);
+/// assert!(*x != *z);
+/// assert!(x.borrowed_dyn_eq(y));
+/// ```
+pub trait BorrowedDynEq<'a>: private::BorrowedTypeId<'a> + 'a {
+    /// Compares `self` and `other` for equality. Returns `false` if the
+    /// underlying concrete types differ.
+    fn borrowed_dyn_eq(&self, other: &dyn BorrowedDynEq<'a>) -> bool;
+}
+
+impl<'a, T: BorrowedType<'a> + PartialEq> BorrowedDynEq<'a> for T {
+    fn borrowed_dyn_eq(&self, other: &dyn BorrowedDynEq<'a>) -> bool {
+        downcast_borrowed::<T>(other).is_some_and(|other| self == other)
+    }
+}
+
+impl<'a> PartialEq for dyn BorrowedDynEq<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.borrowed_dyn_eq(other)
+    }
+}
+
+/// A trait for comparing dynamically-typed values for ordering, which may
+/// contain borrows of lifetime `'a`.
+///
+/// This is the non-`'static` counterpart of `DynOrd`. It is implemented
+/// for every type that is `PartialOrd` and [`BorrowedType`]. As with
+/// `DynOrd`, values of different types are incomparable.
+///
+/// ```
+/// # use core::cmp::Ordering;
+/// # use dyn_ord::BorrowedDynOrd;
+/// let text = String::from("bar qux");
+/// let words: Vec<&str> = text.split(' ').collect();
+///
+/// let x: &dyn BorrowedDynOrd<'_> = &words[0];
+/// let y: &dyn BorrowedDynOrd<'_> = &words[1];
+/// let z: &dyn BorrowedDynOrd<'_> = &42;
+///
+/// assert!(*x < *y);
+/// assert!(*x == *(&"bar" as &dyn BorrowedDynOrd<'_>));
+/// assert_eq!(x.borrowed_dyn_ord(y), Some(Ordering::Less));
+/// assert_eq!(x.partial_cmp(z), None);
+/// ```
+pub trait BorrowedDynOrd<'a>: BorrowedDynEq<'a> {
+    /// Compares `self` and `other`. Returns `None` if the underlying
+    /// concrete types differ, or if the values are incomparable.
+    fn borrowed_dyn_ord(&self, other: &dyn BorrowedDynOrd<'a>) -> Option<Ordering>;
+}
+
+impl<'a, T: BorrowedType<'a> + PartialOrd> BorrowedDynOrd<'a> for T {
+    fn borrowed_dyn_ord(&self, other: &dyn BorrowedDynOrd<'a>) -> Option<Ordering> {
+        self.partial_cmp(downcast_borrowed::<T>(other)?)
+    }
+}
+
+impl<'a> PartialEq for dyn BorrowedDynOrd<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+impl<'a> PartialOrd for dyn BorrowedDynOrd<'a> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.borrowed_dyn_ord(other)
+    }
+}
+
+/// Returns the concrete value underlying a trait object if it is of type `T`.
+fn downcast_borrowed<'a, 'b, T: BorrowedType<'a>>(x: &'b dyn BorrowedDynEq<'a>) -> Option<&'b T> {
+    if x.borrowed_type_id() == TypeId::of::<T::Static>() {
+        // SAFETY: the `TypeId`s of the `'static` versions of the types are
+        // equal, which by the contract of `BorrowedType` means that the
+        // underlying type is `T`, up to the lifetime `'a`. Both the trait
+        // object and `T` use the same `'a`, so the types are identical.
+        Some(unsafe { &*(x as *const dyn BorrowedDynEq<'a> as *const T) })
+    } else {
+        None
+    }
+}
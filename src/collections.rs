@@ -0,0 +1,641 @@
+//! Heterogeneous collections of dynamically-typed values.
+
+use core::fmt;
+use core::any::Any;
+use core::ops::{Bound, RangeBounds};
+use alloc::boxed::Box;
+use alloc::collections::{BTreeSet, BTreeMap, BinaryHeap};
+use alloc::vec::Vec;
+use crate::{DynEq, DynOrd, DynTotalOrd, OrdBox};
+#[cfg(feature = "std")]
+use std::collections::{HashSet, HashMap};
+#[cfg(feature = "std")]
+use crate::{DynHash, EqBox};
+
+/// A hash set of values of (potentially) different types.
+///
+/// This is a thin wrapper around a `HashSet<EqBox<Box<dyn DynHash>>>`,
+/// which allows looking up and removing elements by reference, without
+/// having to box them. Values of different types are never equal, so
+/// `42_i32` and `42_u64` are distinct elements.
+///
+/// ```
+/// # use dyn_ord::DynHashSet;
+/// let mut set = DynHashSet::new();
+///
+/// assert!(set.insert(Box::new(42)));
+/// assert!(set.insert(Box::new(42_u64)));
+/// assert!(set.insert(Box::new("qux")));
+/// assert!(!set.insert(Box::new(42)));
+/// assert_eq!(set.len(), 3);
+///
+/// assert!(set.contains(&"qux"));
+/// assert!(!set.contains(&String::from("qux")));
+///
+/// assert!(set.remove(&42_u64));
+/// assert!(!set.remove(&42_u64));
+/// assert_eq!(set.len(), 2);
+///
+/// let ints: Vec<i32> = set.iter().filter_map(|x| x.as_any().downcast_ref()).copied().collect();
+/// assert_eq!(ints, [42]);
+/// ```
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct DynHashSet {
+    inner: HashSet<EqBox<Box<dyn DynHash>>>,
+}
+
+#[cfg(feature = "std")]
+impl DynHashSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value to the set. Returns `true` if the value was not
+    /// already present, and `false` otherwise, in which case the set is
+    /// left unchanged.
+    pub fn insert(&mut self, val: Box<dyn DynHash>) -> bool {
+        self.inner.insert(EqBox(val))
+    }
+
+    /// Returns `true` if the set contains a value that is equal to `val`.
+    pub fn contains(&self, val: &dyn DynHash) -> bool {
+        self.inner.contains(val)
+    }
+
+    /// Removes a value from the set. Returns `true` if the value was present.
+    pub fn remove(&mut self, val: &dyn DynHash) -> bool {
+        self.inner.remove(val)
+    }
+
+    /// Returns the number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns an iterator over the elements of the set, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn DynHash> {
+        self.inner.iter().map(|val| &*val.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for DynHashSet {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_set()
+            .entries(self.iter().map(|val| val.as_dyn_eq()))
+            .finish()
+    }
+}
+
+/// A hash map with keys of (potentially) different types.
+///
+/// This is a thin wrapper around a `HashMap<EqBox<Box<dyn DynHash>>, V>`,
+/// which allows looking up and removing entries by a reference to the
+/// key, without having to box it. Keys of different types are never
+/// equal, so `42_i32` and `42_u64` are distinct keys.
+///
+/// ```
+/// # use dyn_ord::DynHashMap;
+/// let mut map = DynHashMap::new();
+///
+/// assert_eq!(map.insert(Box::new(42), "int"), None);
+/// assert_eq!(map.insert(Box::new("qux"), "str"), None);
+/// assert_eq!(map.insert(Box::new(42), "i32"), Some("int"));
+/// assert_eq!(map.len(), 2);
+///
+/// assert_eq!(map.get(&42), Some(&"i32"));
+/// assert_eq!(map.get(&42_u64), None);
+/// assert!(map.contains_key(&"qux"));
+///
+/// if let Some(val) = map.get_mut(&"qux") {
+///     *val = "string";
+/// }
+///
+/// assert_eq!(map.remove(&"qux"), Some("string"));
+/// assert_eq!(map.remove(&"qux"), None);
+///
+/// let entries: Vec<(i32, &str)> = map
+///     .iter()
+///     .filter_map(|(key, val)| key.as_any().downcast_ref().map(|&key| (key, *val)))
+///     .collect();
+///
+/// assert_eq!(entries, [(42, "i32")]);
+/// ```
+#[cfg(feature = "std")]
+pub struct DynHashMap<V> {
+    inner: HashMap<EqBox<Box<dyn DynHash>>, V>,
+}
+
+#[cfg(feature = "std")]
+impl<V> DynHashMap<V> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        DynHashMap { inner: HashMap::new() }
+    }
+
+    /// Inserts a key-value pair into the map. If the map already contained
+    /// an equal key, its value is replaced and the old value is returned.
+    /// The key itself is not updated in this case.
+    pub fn insert(&mut self, key: Box<dyn DynHash>, val: V) -> Option<V> {
+        self.inner.insert(EqBox(key), val)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get(&self, key: &dyn DynHash) -> Option<&V> {
+        self.inner.get(key)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut(&mut self, key: &dyn DynHash) -> Option<&mut V> {
+        self.inner.get_mut(key)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key(&self, key: &dyn DynHash) -> bool {
+        self.inner.contains_key(key)
+    }
+
+    /// Removes a key from the map, returning its value if it was present.
+    pub fn remove(&mut self, key: &dyn DynHash) -> Option<V> {
+        self.inner.remove(key)
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns an iterator over the entries of the map, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&dyn DynHash, &V)> {
+        self.inner.iter().map(|(key, val)| (&*key.0, val))
+    }
+
+    /// Returns an iterator over the entries of the map, in arbitrary order,
+    /// with mutable references to the values.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&dyn DynHash, &mut V)> {
+        self.inner.iter_mut().map(|(key, val)| (&*key.0, val))
+    }
+
+    /// Returns an iterator over the keys of the map, in arbitrary order.
+    pub fn keys(&self) -> impl Iterator<Item = &dyn DynHash> {
+        self.inner.keys().map(|key| &*key.0)
+    }
+
+    /// Returns an iterator over the values of the map, in arbitrary order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.inner.values()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V> Default for DynHashMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: fmt::Debug> fmt::Debug for DynHashMap<V> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_map()
+            .entries(self.iter().map(|(key, val)| (key.as_dyn_eq(), val)))
+            .finish()
+    }
+}
+
+/// An ordered set of values of (potentially) different types.
+///
+/// This is a thin wrapper around a `BTreeSet<OrdBox<Box<dyn DynTotalOrd>>>`.
+/// Elements are ordered by their type first, then by their value, so
+/// elements of the same type are contiguous and sorted when iterating.
+/// The relative order of different types is arbitrary, but consistent
+/// within a single run of the program.
+///
+/// ```
+/// # use dyn_ord::DynEqSet;
+/// let mut set = DynEqSet::new();
+///
+/// assert!(set.insert(3));
+/// assert!(set.insert("qux"));
+/// assert!(set.insert(1));
+/// assert!(set.insert("bar"));
+/// assert!(!set.insert(3));
+/// assert_eq!(set.len(), 4);
+///
+/// assert!(set.contains(&"bar"));
+/// assert!(!set.contains(&3_u8));
+///
+/// assert!(set.remove(&"qux"));
+/// assert!(!set.remove(&"qux"));
+///
+/// let ints: Vec<i32> = set.iter().filter_map(|x| x.downcast_ref()).copied().collect();
+/// assert_eq!(ints, [1, 3]);
+/// ```
+#[derive(Default)]
+pub struct DynEqSet {
+    inner: BTreeSet<OrdBox<Box<dyn DynTotalOrd>>>,
+}
+
+impl DynEqSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value to the set. Returns `true` if the value was not
+    /// already present, and `false` otherwise, in which case the set is
+    /// left unchanged.
+    pub fn insert<T: Any + Ord>(&mut self, val: T) -> bool {
+        self.inner.insert(OrdBox(Box::new(val)))
+    }
+
+    /// Returns `true` if the set contains a value that is equal to `val`.
+    pub fn contains<T: Any + Ord>(&self, val: &T) -> bool {
+        self.inner.contains(val as &dyn DynTotalOrd)
+    }
+
+    /// Removes a value from the set. Returns `true` if the value was present.
+    pub fn remove<T: Any + Ord>(&mut self, val: &T) -> bool {
+        self.inner.remove(val as &dyn DynTotalOrd)
+    }
+
+    /// Returns the number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the set contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns an iterator over the elements of the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn DynOrd> {
+        self.inner.iter().map(|val| &*val.0 as &dyn DynOrd)
+    }
+}
+
+impl fmt::Debug for DynEqSet {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// An ordered map with keys of (potentially) different types.
+///
+/// This is a thin wrapper around a `BTreeMap<OrdBox<Box<dyn DynTotalOrd>>, V>`.
+/// Keys are ordered in the same way as the elements of a `DynEqSet`:
+/// by their type first, then by their value.
+///
+/// ```
+/// # use dyn_ord::DynOrdMap;
+/// let mut map = DynOrdMap::new();
+///
+/// assert_eq!(map.insert(3, "three"), None);
+/// assert_eq!(map.insert("qux", "str"), None);
+/// assert_eq!(map.insert(1, "one"), None);
+/// assert_eq!(map.insert(5, "five"), None);
+/// assert_eq!(map.insert(3, "drei"), Some("three"));
+/// assert_eq!(map.len(), 4);
+///
+/// assert_eq!(map.get(&3), Some(&"drei"));
+/// assert_eq!(map.get(&3_u8), None);
+/// assert_eq!(map.remove(&"qux"), Some("str"));
+///
+/// let small: Vec<&str> = map.range(..4).map(|(_, val)| *val).collect();
+/// assert_eq!(small, ["one", "drei"]);
+///
+/// let keys: Vec<i32> = map.keys().filter_map(|key| key.downcast_ref()).copied().collect();
+/// assert_eq!(keys, [1, 3, 5]);
+/// ```
+pub struct DynOrdMap<V> {
+    inner: BTreeMap<OrdBox<Box<dyn DynTotalOrd>>, V>,
+}
+
+impl<V> DynOrdMap<V> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        DynOrdMap { inner: BTreeMap::new() }
+    }
+
+    /// Inserts a key-value pair into the map. If the map already contained
+    /// an equal key, its value is replaced and the old value is returned.
+    /// The key itself is not updated in this case.
+    pub fn insert<K: Any + Ord>(&mut self, key: K, val: V) -> Option<V> {
+        self.inner.insert(OrdBox(Box::new(key)), val)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<K: Any + Ord>(&self, key: &K) -> Option<&V> {
+        self.inner.get(key as &dyn DynTotalOrd)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<K: Any + Ord>(&mut self, key: &K) -> Option<&mut V> {
+        self.inner.get_mut(key as &dyn DynTotalOrd)
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    pub fn contains_key<K: Any + Ord>(&self, key: &K) -> bool {
+        self.inner.contains_key(key as &dyn DynTotalOrd)
+    }
+
+    /// Removes a key from the map, returning its value if it was present.
+    pub fn remove<K: Any + Ord>(&mut self, key: &K) -> Option<V> {
+        self.inner.remove(key as &dyn DynTotalOrd)
+    }
+
+    /// Returns the number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the map contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns an iterator over the entries of the map whose keys are of
+    /// type `K` and lie within `range`, in ascending order of the keys.
+    ///
+    /// Keys of other types are never yielded, not even if one or both
+    /// ends of the range are unbounded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if
+    /// they are equal and both excluded.
+    pub fn range<K, R>(&self, range: R) -> impl Iterator<Item = (&dyn DynOrd, &V)>
+    where
+        K: Any + Ord,
+        R: RangeBounds<K>,
+    {
+        let start = range.start_bound().map(|key| key as &dyn DynTotalOrd);
+        let end = range.end_bound().map(|key| key as &dyn DynTotalOrd);
+
+        // Keys of the same type are contiguous, so the unbounded ends of
+        // the range are trimmed by skipping the keys of other types.
+        self.inner
+            .range::<dyn DynTotalOrd, (Bound<_>, Bound<_>)>((start, end))
+            .map(|(key, val)| (&*key.0 as &dyn DynOrd, val))
+            .skip_while(|(key, _)| !key.is::<K>())
+            .take_while(|(key, _)| key.is::<K>())
+    }
+
+    /// Returns an iterator over the entries of the map, in ascending order
+    /// of the keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&dyn DynOrd, &V)> {
+        self.inner.iter().map(|(key, val)| (&*key.0 as &dyn DynOrd, val))
+    }
+
+    /// Returns an iterator over the entries of the map, in ascending order
+    /// of the keys, with mutable references to the values.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&dyn DynOrd, &mut V)> {
+        self.inner.iter_mut().map(|(key, val)| (&*key.0 as &dyn DynOrd, val))
+    }
+
+    /// Returns an iterator over the keys of the map, in ascending order.
+    pub fn keys(&self) -> impl Iterator<Item = &dyn DynOrd> {
+        self.inner.keys().map(|key| &*key.0 as &dyn DynOrd)
+    }
+
+    /// Returns an iterator over the values of the map, in ascending order
+    /// of the keys.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.inner.values()
+    }
+}
+
+impl<V> Default for DynOrdMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for DynOrdMap<V> {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// A priority queue (max-heap) of values of (potentially) different types.
+///
+/// This is a thin wrapper around a `BinaryHeap<OrdBox<Box<dyn DynTotalOrd>>>`.
+/// Values are ordered in the same way as the elements of a `DynEqSet`: by
+/// their type first, then by their value. Thus, all values of the type
+/// that is greatest in this arbitrary (but consistent) order of types are
+/// popped first, in descending order, followed by the values of the next
+/// type, and so on.
+///
+/// ```
+/// # use dyn_ord::DynBinaryHeap;
+/// let mut heap = DynBinaryHeap::new();
+///
+/// heap.push(1);
+/// heap.push(3);
+/// heap.push(2);
+///
+/// assert_eq!(heap.len(), 3);
+/// assert_eq!(heap.peek().and_then(|x| x.downcast_ref::<i32>()), Some(&3));
+///
+/// let popped: Vec<i32> = std::iter::from_fn(|| heap.pop())
+///     .filter_map(|x| x.downcast_ref().copied())
+///     .collect();
+///
+/// assert_eq!(popped, [3, 2, 1]);
+/// assert!(heap.is_empty());
+/// ```
+#[derive(Default)]
+pub struct DynBinaryHeap {
+    inner: BinaryHeap<OrdBox<Box<dyn DynTotalOrd>>>,
+}
+
+impl DynBinaryHeap {
+    /// Creates an empty heap.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pushes a value onto the heap.
+    pub fn push<T: Any + Ord>(&mut self, val: T) {
+        self.inner.push(OrdBox(Box::new(val)));
+    }
+
+    /// Removes the greatest value from the heap and returns it, or `None`
+    /// if the heap is empty.
+    pub fn pop(&mut self) -> Option<Box<dyn DynOrd>> {
+        self.inner.pop().map(|val| val.0 as Box<dyn DynOrd>)
+    }
+
+    /// Returns the greatest value in the heap, or `None` if it is empty.
+    pub fn peek(&self) -> Option<&dyn DynOrd> {
+        self.inner.peek().map(|val| &*val.0 as &dyn DynOrd)
+    }
+
+    /// Returns the number of values in the heap.
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns an iterator over the values in the heap, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn DynOrd> {
+        self.inner.iter().map(|val| &*val.0 as &dyn DynOrd)
+    }
+}
+
+impl fmt::Debug for DynBinaryHeap {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// A union-find (disjoint-set) structure over values of (potentially)
+/// different types.
+///
+/// Values are identified by `DynEq` equality, and each value belongs to
+/// exactly one equivalence class. Classes are identified by the index of
+/// their representative element, which may change when classes are merged.
+///
+/// Since `DynEq` values can't be hashed, looking up a value takes time
+/// linear in the number of elements. Merging and finding classes by
+/// index takes logarithmic time.
+///
+/// ```
+/// # use dyn_ord::DynEqUnionFind;
+/// let mut classes = DynEqUnionFind::new();
+///
+/// let one = classes.insert(Box::new(1));
+/// let uno = classes.insert(Box::new("uno"));
+/// let two = classes.insert(Box::new(2));
+///
+/// assert_eq!(classes.insert(Box::new(1)), one);
+/// assert_eq!(classes.len(), 3);
+///
+/// assert_eq!(classes.union(&1, &"uno"), classes.find(&"uno"));
+/// assert!(classes.same_class(&1, &"uno"));
+/// assert!(!classes.same_class(&"uno", &2));
+///
+/// assert_eq!(classes.find_index(one), classes.find_index(uno));
+/// assert_ne!(classes.find_index(one), classes.find_index(two));
+/// assert_eq!(classes.find(&3), None);
+/// assert_eq!(classes.union(&2, &3), None);
+/// ```
+#[derive(Default, Debug)]
+pub struct DynEqUnionFind {
+    values: Vec<Box<dyn DynEq>>,
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+}
+
+impl DynEqUnionFind {
+    /// Creates an empty structure.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a value in a new class of its own, and returns its index.
+    /// If an equal value is already present, returns its index instead,
+    /// and leaves the structure unchanged.
+    pub fn insert(&mut self, val: Box<dyn DynEq>) -> usize {
+        if let Some(index) = self.index_of(&*val) {
+            return index;
+        }
+
+        let index = self.values.len();
+
+        self.values.push(val);
+        self.parents.push(index);
+        self.ranks.push(0);
+
+        index
+    }
+
+    /// Returns the index of the value equal to `val`, if any.
+    pub fn index_of(&self, val: &dyn DynEq) -> Option<usize> {
+        self.values.iter().position(|x| **x == *val)
+    }
+
+    /// Returns the value at `index`, if any.
+    pub fn get(&self, index: usize) -> Option<&dyn DynEq> {
+        self.values.get(index).map(|val| &**val)
+    }
+
+    /// Returns the index of the representative of the class containing
+    /// the element at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn find_index(&self, mut index: usize) -> usize {
+        while self.parents[index] != index {
+            index = self.parents[index];
+        }
+        index
+    }
+
+    /// Returns the index of the representative of the class containing
+    /// `val`, or `None` if `val` is not present.
+    pub fn find(&self, val: &dyn DynEq) -> Option<usize> {
+        self.index_of(val).map(|index| self.find_index(index))
+    }
+
+    /// Merges the classes containing `a` and `b`, and returns the index
+    /// of the representative of the merged class. Returns `None` and
+    /// leaves the structure unchanged if either value is not present.
+    pub fn union(&mut self, a: &dyn DynEq, b: &dyn DynEq) -> Option<usize> {
+        let a = self.find(a)?;
+        let b = self.find(b)?;
+
+        if a == b {
+            return Some(a);
+        }
+
+        // attach the shallower tree to the deeper one
+        let (root, child) = if self.ranks[a] < self.ranks[b] { (b, a) } else { (a, b) };
+
+        self.parents[child] = root;
+
+        if self.ranks[root] == self.ranks[child] {
+            self.ranks[root] += 1;
+        }
+
+        Some(root)
+    }
+
+    /// Returns `true` if `a` and `b` are both present and in the same class.
+    pub fn same_class(&self, a: &dyn DynEq, b: &dyn DynEq) -> bool {
+        match (self.find(a), self.find(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
@@ -1,7 +1,40 @@
 //! Traits for dynamically-typed equality comparison and ordering.
+//!
+//! This crate is `no_std`, but it requires `alloc`. The `std` feature,
+//! which is enabled by default, adds the items that depend on hash-based
+//! collections, such as [`DynHashSet`] and [`DynHashMap`].
 
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+use alloc::boxed::Box;
+use core::fmt;
 use core::cmp::Ordering;
-use core::any::Any;
+use core::any::{Any, TypeId};
+use core::hash::{Hash, Hasher};
+
+pub mod util;
+#[doc(hidden)]
+pub mod macros;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod wrapper;
+mod collections;
+mod borrowed;
+mod registry;
+
+pub use wrapper::{EqBox, OrdBox, NaNIsMin, NaNIsMax};
+pub use borrowed::{BorrowedType, BorrowedDynEq, BorrowedDynOrd};
+pub use registry::DynEqRegistry;
+pub use collections::{DynEqSet, DynOrdMap, DynBinaryHeap, DynEqUnionFind};
+#[cfg(feature = "std")]
+pub use collections::{DynHashSet, DynHashMap};
+
+#[cfg(feature = "derive")]
+pub use dyn_ord_derive::{DynEq, DynOrd};
 
 /// A trait for comparing dynamically-typed values for equality.
 ///
@@ -31,12 +64,133 @@ use core::any::Any;
 /// assert!(*z != *y);
 /// assert!(*z == *z);
 /// ```
+///
+/// Trait objects with additional `Send` and `Sync` bounds can be
+/// compared in the same manner:
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use std::thread;
+/// # use dyn_ord::DynEq;
+/// let x: Arc<dyn DynEq + Send + Sync> = Arc::new(42);
+/// let y: Box<dyn DynEq + Send> = Box::new(42);
+/// let z: Box<dyn DynEq + Send> = Box::new(String::from("qux"));
+///
+/// let x2 = Arc::clone(&x);
+/// thread::spawn(move || assert!(*x2 == *x2)).join().unwrap();
+///
+/// assert!(*x == *x);
+/// assert!(*y != *z);
+/// ```
+///
+/// `Box`, `Rc` and `Arc` forward their `PartialEq` impls to their
+/// pointees, so smart pointers to `dyn DynEq` can be compared directly.
+/// Due to a [compiler quirk](https://github.com/rust-lang/rust/issues/31740),
+/// `x == y` moves `y` when comparing smart pointers to trait objects,
+/// so compare references (`&x == &y`) or pointees (`*x == *y`) instead
+/// if the pointers are to be used afterwards.
+///
+/// ```
+/// # use std::rc::Rc;
+/// # use std::sync::Arc;
+/// # use dyn_ord::DynEq;
+/// let b1: Box<dyn DynEq> = Box::new(42);
+/// let b2: Box<dyn DynEq> = Box::new(42);
+/// assert!(&b1 == &b2);
+/// assert!(b1 == b2);
+///
+/// let r1: Rc<dyn DynEq> = Rc::new(42);
+/// let r2: Rc<dyn DynEq> = Rc::new('x');
+/// assert!(&r1 != &r2);
+/// assert!(r1 != r2);
+///
+/// let a1: Arc<dyn DynEq> = Arc::new(String::from("qux"));
+/// let a2: Arc<dyn DynEq> = Arc::new(String::from("qux"));
+/// assert!(&a1 == &a2);
+/// assert!(a1 == a2);
+/// ```
+///
+/// `DynEq` is implemented for every `'static` type that implements
+/// `PartialEq`. This covers wrappers and pointers (`Pin`, `NonNull`,
+/// `Wrapping`), cells, collections and `Box<dyn DynEq>` itself. Each of
+/// these is a distinct type from the one it wraps, so e.g. `Wrapping(42)`
+/// is never equal to `42`. Comparing a `RefCell` borrows its contents,
+/// which panics if the cell is currently mutably borrowed.
+///
+/// Types that don't implement `PartialEq` can derive `DynEq` instead,
+/// comparing their fields using `DynEq`, if the `derive` feature is
+/// enabled. Foreign types without `PartialEq`, such as `Mutex` or the
+/// atomic types, can't be given an explicit impl, since it would conflict
+/// with the blanket impl; wrap them in a newtype that implements
+/// `PartialEq` instead.
+///
+/// Unsized types, such as slices, can not be coerced to trait objects,
+/// because a pointer to a trait object has no room for the length of
+/// the slice. Therefore, `DynEq` is only implemented for sized types.
+/// Slices, strings and paths can still be compared dynamically behind a
+/// (sized) pointer, such as `Box<[T]>`, `&'static str` or `PathBuf` and
+/// `&'static Path`. Note that these are distinct types, so they never
+/// compare equal to each other.
+///
+/// ```compile_fail,E0277
+/// # use dyn_ord::DynEq;
+/// let slice: &dyn DynEq = &[1, 2, 3][..];
+/// ```
+///
+/// ```compile_fail,E0277
+/// # use dyn_ord::DynEq;
+/// let s: &dyn DynEq = "qux";
+/// ```
+///
+/// ```compile_fail,E0277
+/// # use std::path::Path;
+/// # use dyn_ord::DynEq;
+/// let path: &dyn DynEq = Path::new("/tmp");
+/// ```
+///
+/// Since the underlying type is not required to implement `Debug`, the
+/// `Debug` impl of `dyn DynEq` only prints the name of the type. For the
+/// same reason, `dyn DynEq` is not `Display`; use [`DynEqDisplay`] if the
+/// values need to be displayed.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// let x: Box<dyn DynEq> = Box::new(42);
+///
+/// assert_eq!(format!("{:?}", x), "DynEq(i32)");
+/// ```
 pub trait DynEq: Any {
     #[doc(hidden)]
     fn as_any(&self) -> &dyn Any;
 
+    #[doc(hidden)]
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    #[doc(hidden)]
+    fn dyn_type_name(&self) -> &'static str;
+
+    #[doc(hidden)]
+    fn as_dyn_eq(&self) -> &dyn DynEq;
+
     #[doc(hidden)]
     fn dyn_eq(&self, other: &dyn DynEq) -> bool;
+
+    /// Compares `self` and `other` for equality, distinguishing between
+    /// values of the same type that are not equal, and values of
+    /// different types.
+    ///
+    /// Returns `None` if the underlying concrete types differ, and
+    /// `Some` with the result of `PartialEq::eq` otherwise.
+    ///
+    /// ```
+    /// # use dyn_ord::DynEq;
+    /// let x: &dyn DynEq = &42;
+    ///
+    /// assert_eq!(x.dyn_eq_typed(&42), Some(true));
+    /// assert_eq!(x.dyn_eq_typed(&43), Some(false));
+    /// assert_eq!(x.dyn_eq_typed(&42_u64), None);
+    /// ```
+    fn dyn_eq_typed(&self, other: &dyn DynEq) -> Option<bool>;
 }
 
 impl<T: Any + PartialEq> DynEq for T {
@@ -44,12 +198,27 @@ impl<T: Any + PartialEq> DynEq for T {
         self
     }
 
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn dyn_type_name(&self) -> &'static str {
+        core::any::type_name::<T>()
+    }
+
+    fn as_dyn_eq(&self) -> &dyn DynEq {
+        self
+    }
+
     fn dyn_eq(&self, other: &dyn DynEq) -> bool {
-        if let Some(other) = other.as_any().downcast_ref::<T>() {
-            *self == *other
-        } else {
-            false
-        }
+        self.dyn_eq_typed(other).unwrap_or(false)
+    }
+
+    fn dyn_eq_typed(&self, other: &dyn DynEq) -> Option<bool> {
+        other
+            .as_any()
+            .downcast_ref::<T>()
+            .map(|other| *self == *other)
     }
 }
 
@@ -59,6 +228,215 @@ impl PartialEq for dyn DynEq + '_ {
     }
 }
 
+impl PartialEq for dyn DynEq + Send + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl PartialEq for dyn DynEq + Sync + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl PartialEq for dyn DynEq + Send + Sync + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+/// Compares a trait object with a value of a concrete type directly,
+/// without coercing the value to a trait object. This is consistent with
+/// comparing two trait objects: if the underlying type is `T`, the values
+/// are compared using `PartialEq`, otherwise they are not equal.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// let x: &dyn DynEq = &42;
+/// let y: Box<dyn DynEq + Send> = Box::new(String::from("qux"));
+///
+/// assert!(*x == 42);
+/// assert!(*x != 43);
+/// assert!(*x != 42_u64);
+/// assert!(*y == String::from("qux"));
+/// assert!(*y != "qux");
+/// ```
+impl<T: Any + PartialEq> PartialEq<T> for dyn DynEq + '_ {
+    fn eq(&self, other: &T) -> bool {
+        self.as_any().downcast_ref::<T>() == Some(other)
+    }
+}
+
+impl<T: Any + PartialEq> PartialEq<T> for dyn DynEq + Send + '_ {
+    fn eq(&self, other: &T) -> bool {
+        self.as_any().downcast_ref::<T>() == Some(other)
+    }
+}
+
+impl<T: Any + PartialEq> PartialEq<T> for dyn DynEq + Sync + '_ {
+    fn eq(&self, other: &T) -> bool {
+        self.as_any().downcast_ref::<T>() == Some(other)
+    }
+}
+
+impl<T: Any + PartialEq> PartialEq<T> for dyn DynEq + Send + Sync + '_ {
+    fn eq(&self, other: &T) -> bool {
+        self.as_any().downcast_ref::<T>() == Some(other)
+    }
+}
+
+impl dyn DynEq + '_ {
+    /// Returns `true` if the underlying concrete type is `T`.
+    ///
+    /// ```
+    /// # use dyn_ord::DynEq;
+    /// let x: &dyn DynEq = &42;
+    ///
+    /// assert!(x.is::<i32>());
+    /// assert!(!x.is::<u32>());
+    /// ```
+    pub fn is<T: Any>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    /// Returns a reference to the underlying value if it is of type `T`,
+    /// or `None` if it isn't.
+    ///
+    /// ```
+    /// # use dyn_ord::DynEq;
+    /// let x: &dyn DynEq = &String::from("qux");
+    ///
+    /// assert_eq!(x.downcast_ref::<String>().map(String::as_str), Some("qux"));
+    /// assert_eq!(x.downcast_ref::<i32>(), None);
+    /// ```
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Returns a mutable reference to the underlying value if it is of
+    /// type `T`, or `None` if it isn't.
+    ///
+    /// ```
+    /// # use dyn_ord::DynEq;
+    /// let mut x: Box<dyn DynEq> = Box::new(42);
+    ///
+    /// *x.downcast_mut::<i32>().unwrap() += 1;
+    ///
+    /// assert_eq!(x.downcast_ref::<i32>(), Some(&43));
+    /// assert_eq!(x.downcast_mut::<u8>(), None);
+    /// ```
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+impl fmt::Debug for dyn DynEq + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynEq({})", self.dyn_type_name())
+    }
+}
+
+impl fmt::Debug for dyn DynEq + Send + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynEq({})", self.dyn_type_name())
+    }
+}
+
+impl fmt::Debug for dyn DynEq + Sync + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynEq({})", self.dyn_type_name())
+    }
+}
+
+impl fmt::Debug for dyn DynEq + Send + Sync + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynEq({})", self.dyn_type_name())
+    }
+}
+
+/// A trait for comparing dynamically-typed values for equality and
+/// displaying them.
+///
+/// Whether a type implements `Display` can't be determined at runtime,
+/// so it has to be required when the trait object is created. This is a
+/// single bound (and trait object type) combining `DynEq` and `Display`,
+/// and `dyn DynEqDisplay` is displayed by the `Display` impl of the
+/// underlying value. It is implemented for every type that is `DynEq`
+/// and `Display`, and it can be upcast to `dyn DynEq`.
+///
+/// ```
+/// # use dyn_ord::{DynEq, DynEqDisplay, boxed_display};
+/// let x: Box<dyn DynEqDisplay> = boxed_display(42);
+/// let y: Box<dyn DynEqDisplay> = boxed_display("qux");
+///
+/// assert_eq!(x.to_string(), "42");
+/// assert_eq!(format!("{} {:?}", y, y), "qux DynEq(&str)");
+/// assert!(*x != *y);
+///
+/// let z: &dyn DynEq = &*x;
+/// assert!(*z == 42);
+/// ```
+pub trait DynEqDisplay: DynEq + fmt::Display {}
+
+impl<T: DynEq + fmt::Display> DynEqDisplay for T {}
+
+impl PartialEq for dyn DynEqDisplay + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl fmt::Debug for dyn DynEqDisplay + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynEq({})", self.dyn_type_name())
+    }
+}
+
+/// Boxes a value as a `dyn DynEqDisplay` trait object.
+///
+/// ```
+/// # use dyn_ord::boxed_display;
+/// let values = [boxed_display(1.5), boxed_display('x'), boxed_display("qux")];
+/// let strings: Vec<String> = values.iter().map(ToString::to_string).collect();
+///
+/// assert_eq!(strings, ["1.5", "x", "qux"]);
+/// ```
+pub fn boxed_display<T: DynEq + fmt::Display>(value: T) -> Box<dyn DynEqDisplay> {
+    Box::new(value)
+}
+
+/// A trait for comparing dynamically-typed values for total equality.
+///
+/// `dyn DynEq` can not be `Eq`, because the blanket impl covers
+/// types, such as floating-point numbers, that only implement
+/// `PartialEq`. `DynTotalEq` is only implemented for types that
+/// are `Eq`, so `dyn DynTotalEq` is `Eq` as well.
+///
+/// ```
+/// # use dyn_ord::DynTotalEq;
+/// fn assert_total_eq<T: Eq + ?Sized>(_: &T) {}
+///
+/// let x: Box<dyn DynTotalEq> = Box::new(42);
+/// let y: Box<dyn DynTotalEq> = Box::new(String::from("qux"));
+///
+/// assert_total_eq(&*x);
+/// assert!(*x == *x);
+/// assert!(*x != *y);
+/// assert!(*y == *(Box::new(String::from("qux")) as Box<dyn DynTotalEq>));
+/// ```
+pub trait DynTotalEq: DynEq {}
+
+impl<T: Any + Eq> DynTotalEq for T {}
+
+impl PartialEq for dyn DynTotalEq + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn DynTotalEq + '_ {}
+
 /// A trait for comparing dynamically-typed values for ordering.
 ///
 /// After coercing your values to a trait object of type `DynOrd`,
@@ -82,9 +460,78 @@ impl PartialEq for dyn DynEq + '_ {
 /// assert_eq!(y.partial_cmp(&y), Some(Ordering::Equal));
 /// assert_eq!(x.partial_cmp(&y), None);
 /// ```
+///
+/// Since `DynEq` is a supertrait of `DynOrd`, a single `DynOrd` bound
+/// (or trait object type) suffices for values that need to be compared
+/// both for equality and for ordering. Trait objects with additional
+/// `Send` and `Sync` bounds can be compared in the same manner.
+///
+/// As with `DynEq`, `Box`, `Rc` and `Arc` forward their `PartialEq` and
+/// `PartialOrd` impls to their pointees, and binary operators move
+/// their right-hand side, so compare references or pointees if the
+/// pointers need to be used afterwards.
+///
+/// ```
+/// # use core::cmp::Ordering;
+/// # use std::rc::Rc;
+/// # use std::sync::Arc;
+/// # use dyn_ord::DynOrd;
+/// let b1: Box<dyn DynOrd> = Box::new(1);
+/// let b2: Box<dyn DynOrd> = Box::new(2);
+/// assert!(&b1 < &b2);
+/// assert!(&b1 != &b2);
+/// assert_eq!(b1.partial_cmp(&b2), Some(Ordering::Less));
+///
+/// let r1: Rc<dyn DynOrd> = Rc::new(String::from("qux"));
+/// let r2: Rc<dyn DynOrd> = Rc::new(String::from("baz"));
+/// assert!(&r1 >= &r2);
+/// assert_eq!(r1.partial_cmp(&r2), Some(Ordering::Greater));
+///
+/// let a1: Arc<dyn DynOrd> = Arc::new(1.5);
+/// let a2: Arc<dyn DynOrd> = Arc::new('x');
+/// assert!(&a1 != &a2);
+/// assert_eq!(a1.partial_cmp(&a2), None);
+/// ```
+///
+/// `DynOrd` is implemented for every `'static` type that implements
+/// `PartialOrd`. Wrappers such as `Reverse` or `Pin<Box<T>>` are distinct
+/// types from the values they wrap, so a `Reverse<i32>` is incomparable
+/// with an `i32`. Types that don't implement `PartialOrd` can derive
+/// `DynOrd` instead, comparing their fields lexicographically using
+/// `DynOrd`, if the `derive` feature is enabled.
+///
+/// `core::ops::Range` implements `PartialEq` but not `PartialOrd`, so a
+/// range is `DynEq` but not `DynOrd`. Use the `(start, end)` tuple
+/// instead, which is ordered lexicographically:
+///
+/// ```compile_fail,E0277
+/// # use dyn_ord::DynOrd;
+/// let range: &dyn DynOrd = &(1..5);
+/// ```
+///
+/// Like `DynEq`, `DynOrd` is only implemented for sized types. Slices
+/// and string slices behind a pointer, such as `Box<[T]>`, `&'static [T]`
+/// or `&'static str`, are ordered lexicographically, as usual.
+///
+/// ```compile_fail,E0277
+/// # use dyn_ord::DynOrd;
+/// let slice: &dyn DynOrd = &[1, 2, 3][..];
+/// ```
+///
+/// The `Debug` impl of `dyn DynOrd` prints the name of the type:
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// let x: &dyn DynOrd = &1.5_f32;
+///
+/// assert_eq!(format!("{:?}", x), "DynOrd(f32)");
+/// ```
 pub trait DynOrd: DynEq {
     #[doc(hidden)]
     fn dyn_ord(&self, other: &dyn DynOrd) -> Option<Ordering>;
+
+    #[doc(hidden)]
+    fn as_dyn_ord(&self) -> &dyn DynOrd;
 }
 
 impl<T: Any + PartialOrd> DynOrd for T {
@@ -94,6 +541,10 @@ impl<T: Any + PartialOrd> DynOrd for T {
             .downcast_ref::<T>()
             .and_then(|other| self.partial_cmp(other))
     }
+
+    fn as_dyn_ord(&self) -> &dyn DynOrd {
+        self
+    }
 }
 
 impl PartialEq for dyn DynOrd + '_ {
@@ -107,3 +558,695 @@ impl PartialOrd for dyn DynOrd + '_ {
         self.dyn_ord(other)
     }
 }
+
+impl PartialEq for dyn DynOrd + Send + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+impl PartialOrd for dyn DynOrd + Send + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.dyn_ord(other)
+    }
+}
+
+impl PartialEq for dyn DynOrd + Sync + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+impl PartialOrd for dyn DynOrd + Sync + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.dyn_ord(other)
+    }
+}
+
+impl PartialEq for dyn DynOrd + Send + Sync + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+impl PartialOrd for dyn DynOrd + Send + Sync + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.dyn_ord(other)
+    }
+}
+
+/// Compares a trait object with a value of a concrete type directly,
+/// without coercing the value to a trait object. This is consistent with
+/// comparing two trait objects: if the underlying type is `T`, the values
+/// are compared using `PartialOrd`, otherwise they are incomparable.
+///
+/// ```
+/// # use core::cmp::Ordering;
+/// # use dyn_ord::DynOrd;
+/// let x: &dyn DynOrd = &42;
+/// let y: Box<dyn DynOrd + Send + Sync> = Box::new(2.5);
+///
+/// assert!(*x < 43);
+/// assert!(*x == 42);
+/// assert_eq!(x.partial_cmp(&41), Some(Ordering::Greater));
+/// assert_eq!(x.partial_cmp(&42_u64), None);
+/// assert!(*y >= 2.5);
+/// assert!(*y != f64::NAN);
+/// ```
+impl<T: Any + PartialOrd> PartialEq<T> for dyn DynOrd + '_ {
+    fn eq(&self, other: &T) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+impl<T: Any + PartialOrd> PartialOrd<T> for dyn DynOrd + '_ {
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        self.as_any().downcast_ref::<T>()?.partial_cmp(other)
+    }
+}
+
+impl<T: Any + PartialOrd> PartialEq<T> for dyn DynOrd + Send + '_ {
+    fn eq(&self, other: &T) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+impl<T: Any + PartialOrd> PartialOrd<T> for dyn DynOrd + Send + '_ {
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        self.as_any().downcast_ref::<T>()?.partial_cmp(other)
+    }
+}
+
+impl<T: Any + PartialOrd> PartialEq<T> for dyn DynOrd + Sync + '_ {
+    fn eq(&self, other: &T) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+impl<T: Any + PartialOrd> PartialOrd<T> for dyn DynOrd + Sync + '_ {
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        self.as_any().downcast_ref::<T>()?.partial_cmp(other)
+    }
+}
+
+impl<T: Any + PartialOrd> PartialEq<T> for dyn DynOrd + Send + Sync + '_ {
+    fn eq(&self, other: &T) -> bool {
+        matches!(self.partial_cmp(other), Some(Ordering::Equal))
+    }
+}
+
+impl<T: Any + PartialOrd> PartialOrd<T> for dyn DynOrd + Send + Sync + '_ {
+    fn partial_cmp(&self, other: &T) -> Option<Ordering> {
+        self.as_any().downcast_ref::<T>()?.partial_cmp(other)
+    }
+}
+
+impl dyn DynOrd + '_ {
+    /// Returns `true` if the underlying concrete type is `T`.
+    ///
+    /// ```
+    /// # use dyn_ord::DynOrd;
+    /// let x: &dyn DynOrd = &1.5;
+    ///
+    /// assert!(x.is::<f64>());
+    /// assert!(!x.is::<f32>());
+    /// ```
+    pub fn is<T: Any>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    /// Returns a reference to the underlying value if it is of type `T`,
+    /// or `None` if it isn't.
+    ///
+    /// ```
+    /// # use dyn_ord::DynOrd;
+    /// let x: &dyn DynOrd = &1.5;
+    ///
+    /// assert_eq!(x.downcast_ref::<f64>(), Some(&1.5));
+    /// assert_eq!(x.downcast_ref::<String>(), None);
+    /// ```
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Returns a mutable reference to the underlying value if it is of
+    /// type `T`, or `None` if it isn't.
+    ///
+    /// ```
+    /// # use dyn_ord::DynOrd;
+    /// let mut x: Box<dyn DynOrd> = Box::new(String::from("qux"));
+    ///
+    /// x.downcast_mut::<String>().unwrap().push_str("ux");
+    ///
+    /// assert_eq!(x.downcast_ref::<String>().map(String::as_str), Some("quxux"));
+    /// assert_eq!(x.downcast_mut::<char>(), None);
+    /// ```
+    pub fn downcast_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
+impl fmt::Debug for dyn DynOrd + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynOrd({})", self.dyn_type_name())
+    }
+}
+
+impl fmt::Debug for dyn DynOrd + Send + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynOrd({})", self.dyn_type_name())
+    }
+}
+
+impl fmt::Debug for dyn DynOrd + Sync + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynOrd({})", self.dyn_type_name())
+    }
+}
+
+impl fmt::Debug for dyn DynOrd + Send + Sync + '_ {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DynOrd({})", self.dyn_type_name())
+    }
+}
+
+/// A trait for comparing dynamically-typed values for total ordering.
+///
+/// `DynTotalOrd` is only implemented for types that are `Ord`, and
+/// `dyn DynTotalOrd` is `Ord` as well. Trait objects created from the
+/// same underlying concrete type are compared using `Ord`. Trait objects
+/// created from different concrete types are ordered by their `TypeId`.
+/// The relative order of different types is therefore arbitrary, but it
+/// is consistent within a single run of the program, so heterogeneous
+/// `dyn DynTotalOrd` values can be used as keys in a `BTreeMap`.
+///
+/// ```
+/// # use std::collections::BTreeSet;
+/// # use dyn_ord::DynTotalOrd;
+/// let mut set: BTreeSet<Box<dyn DynTotalOrd>> = BTreeSet::new();
+///
+/// set.insert(Box::new(3));
+/// set.insert(Box::new(String::from("qux")));
+/// set.insert(Box::new(1));
+/// set.insert(Box::new(String::from("baz")));
+/// set.insert(Box::new(3));
+///
+/// assert_eq!(set.len(), 4);
+///
+/// let ints: Vec<i32> = set
+///     .iter()
+///     .filter_map(|x| x.as_any().downcast_ref::<i32>().copied())
+///     .collect();
+///
+/// assert_eq!(ints, [1, 3]);
+/// ```
+pub trait DynTotalOrd: DynTotalEq + DynOrd {}
+
+impl<T: Any + Ord> DynTotalOrd for T {}
+
+impl PartialEq for dyn DynTotalOrd + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn DynTotalOrd + '_ {}
+
+impl PartialOrd for dyn DynTotalOrd + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for dyn DynTotalOrd + '_ {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // values of the same `Ord` type are never incomparable, so unlike
+        // `total_cmp`, this doesn't need to look for NaN-like values
+        let lhs_type = self.as_any().type_id();
+        let rhs_type = other.as_any().type_id();
+
+        lhs_type
+            .cmp(&rhs_type)
+            .then_with(|| self.dyn_ord(other).unwrap_or(Ordering::Equal))
+    }
+}
+
+/// Compares values of different types by their `TypeId`, and values of
+/// the same type using `DynOrd`.
+///
+/// If two values of the same type are incomparable, those that are also
+/// incomparable with themselves (e.g. floating-point NaNs) are ordered
+/// first, and they are equal to each other, so that the order remains
+/// total in their presence. Other incomparable values of the same type
+/// are considered equal; this is only a total order if every such type
+/// is totally ordered apart from self-incomparable values.
+pub(crate) fn total_cmp(lhs: &dyn DynOrd, rhs: &dyn DynOrd) -> Ordering {
+    let lhs_type = lhs.as_any().type_id();
+    let rhs_type = rhs.as_any().type_id();
+
+    lhs_type.cmp(&rhs_type).then_with(|| {
+        if let Some(ordering) = lhs.dyn_ord(rhs) {
+            return ordering;
+        }
+
+        let is_nan = |x: &dyn DynOrd| x.dyn_ord(x).is_none();
+
+        match (is_nan(lhs), is_nan(rhs)) {
+            (true, true) | (false, false) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+        }
+    })
+}
+
+/// A trait for hashing dynamically-typed values.
+///
+/// After coercing your values to a trait object of type `DynHash`,
+/// you can use boxes (and other smart pointers) to instances as keys
+/// in hash-based collections, such as `HashMap` and `HashSet`.
+///
+/// The `TypeId` of the underlying concrete type is hashed before the
+/// value itself, so that values of different types with identical
+/// representations are unlikely to end up in the same bucket.
+///
+/// `dyn DynHash` is also `Eq`, so that it can be used as a key type.
+/// However, the blanket impl only requires `PartialEq`, so reflexivity
+/// is only as good as the underlying `PartialEq` impl. A value that is
+/// not equal to itself, such as a NaN inside a type that hashes its bits,
+/// can be inserted into a `HashMap` more than once, and it can never be
+/// found again. Use [`DynHashEq`], which is only implemented for types
+/// that are `Eq`, if the `Eq` contract must actually hold.
+///
+/// ```
+/// # use core::hash::{Hash, Hasher};
+/// # use std::collections::HashMap;
+/// # use dyn_ord::DynHash;
+/// let mut map: HashMap<Box<dyn DynHash>, &str> = HashMap::new();
+///
+/// map.insert(Box::new(42), "int");
+/// map.insert(Box::new(String::from("qux")), "string");
+/// map.insert(Box::new('x'), "char");
+///
+/// assert_eq!(map[&(Box::new(42) as Box<dyn DynHash>)], "int");
+/// assert_eq!(map[&(Box::new('x') as Box<dyn DynHash>)], "char");
+/// assert_eq!(map.get(&(Box::new(42_u64) as Box<dyn DynHash>)), None);
+///
+/// #[derive(PartialEq)]
+/// struct Bits(f64);
+///
+/// impl Hash for Bits {
+///     fn hash<H: Hasher>(&self, state: &mut H) {
+///         self.0.to_bits().hash(state);
+///     }
+/// }
+///
+/// map.insert(Box::new(Bits(f64::NAN)), "nan");
+/// map.insert(Box::new(Bits(f64::NAN)), "nan");
+///
+/// assert_eq!(map.len(), 5);
+/// assert_eq!(map.get(&(Box::new(Bits(f64::NAN)) as Box<dyn DynHash>)), None);
+/// ```
+pub trait DynHash: DynEq {
+    #[doc(hidden)]
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+}
+
+impl<T: Any + PartialEq + Hash> DynHash for T {
+    fn dyn_hash(&self, state: &mut dyn Hasher) {
+        self.hash(&mut DynEqHasher::new::<T>(state));
+    }
+}
+
+impl PartialEq for dyn DynHash + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn DynHash + '_ {}
+
+impl Hash for dyn DynHash + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dyn_hash(state)
+    }
+}
+
+/// A `Hasher` that prefixes the hashed data with a `TypeId`.
+///
+/// Creating a `DynEqHasher` for type `T` feeds the `TypeId` of `T` to the
+/// wrapped hasher; every subsequent write is forwarded to it unchanged.
+/// Hashing a value through a `DynEqHasher` thus makes it unlikely that
+/// values of different types with identical representations collide,
+/// without having to remember to hash the `TypeId` by hand. This is what
+/// the `DynHash` impl uses.
+///
+/// ```
+/// # use core::hash::{Hash, Hasher};
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use dyn_ord::DynEqHasher;
+/// fn hash_typed<T: Hash + 'static>(value: &T) -> u64 {
+///     let mut state = DynEqHasher::new::<T>(DefaultHasher::new());
+///     value.hash(&mut state);
+///     state.finish()
+/// }
+///
+/// assert_eq!(hash_typed(&42_u32), hash_typed(&42_u32));
+/// assert_ne!(hash_typed(&42_u32), hash_typed(&42_i32));
+/// ```
+#[derive(Clone, Debug)]
+pub struct DynEqHasher<H: Hasher>(H);
+
+impl<H: Hasher> DynEqHasher<H> {
+    /// Wraps `hasher`, feeding it the `TypeId` of `T` first.
+    pub fn new<T: ?Sized + Any>(mut hasher: H) -> Self {
+        TypeId::of::<T>().hash(&mut hasher);
+        DynEqHasher(hasher)
+    }
+
+    /// Returns the wrapped hasher.
+    pub fn into_inner(self) -> H {
+        self.0
+    }
+}
+
+impl<H: Hasher> Hasher for DynEqHasher<H> {
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes)
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.0.write_u8(i)
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.0.write_u16(i)
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.0.write_u32(i)
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0.write_u64(i)
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.0.write_u128(i)
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.0.write_usize(i)
+    }
+}
+
+/// A trait for hashing and comparing dynamically-typed values for
+/// total equality.
+///
+/// This is a single bound (and trait object type) combining `DynTotalEq`
+/// and `DynHash`. It is implemented for every type that is `Eq` and
+/// `Hash`, which makes `dyn DynHashEq` suitable as the key type of
+/// hash-based collections.
+///
+/// ```
+/// # use std::collections::HashSet;
+/// # use dyn_ord::DynHashEq;
+/// fn assert_eq_hash<T: Eq + std::hash::Hash + ?Sized>(_: &T) {}
+///
+/// let mut set: HashSet<Box<dyn DynHashEq>> = HashSet::new();
+///
+/// assert!(set.insert(Box::new(42)));
+/// assert!(set.insert(Box::new("qux")));
+/// assert!(!set.insert(Box::new(42)));
+///
+/// assert_eq_hash::<dyn DynHashEq>(&42);
+/// assert!(set.contains(&(Box::new("qux") as Box<dyn DynHashEq>)));
+/// assert!(!set.contains(&(Box::new(42_u8) as Box<dyn DynHashEq>)));
+/// ```
+pub trait DynHashEq: DynTotalEq + DynHash {}
+
+impl<T: Any + Eq + Hash> DynHashEq for T {}
+
+impl PartialEq for dyn DynHashEq + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn DynHashEq + '_ {}
+
+impl Hash for dyn DynHashEq + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dyn_hash(state)
+    }
+}
+
+/// Returns the `TypeId` of the concrete type underlying a trait object.
+///
+/// ```
+/// # use core::any::TypeId;
+/// # use dyn_ord::{DynEq, type_id_of};
+/// let x: &dyn DynEq = &42;
+///
+/// assert_eq!(type_id_of(x), TypeId::of::<i32>());
+/// assert_ne!(type_id_of(x), TypeId::of::<dyn DynEq>());
+/// ```
+pub fn type_id_of(x: &dyn DynEq) -> TypeId {
+    x.as_any().type_id()
+}
+
+/// Returns the name of the concrete type underlying a trait object.
+///
+/// Like `core::any::type_name`, this is intended for diagnostic use.
+/// The exact contents and format of the returned string are not
+/// specified, and they may change across compiler versions.
+///
+/// ```
+/// # use dyn_ord::{DynEq, type_name_of};
+/// let x: &dyn DynEq = &42;
+/// let y: &dyn DynEq = &String::from("qux");
+///
+/// assert_eq!(type_name_of(x), "i32");
+/// assert!(type_name_of(y).ends_with("String"));
+/// ```
+pub fn type_name_of(x: &dyn DynEq) -> &'static str {
+    x.dyn_type_name()
+}
+
+/// Returns `true` if the two trait objects were created from the same
+/// underlying concrete type.
+///
+/// ```
+/// # use dyn_ord::{DynEq, same_type};
+/// let x: &dyn DynEq = &42;
+/// let y: &dyn DynEq = &1337;
+/// let z: &dyn DynEq = &42_u64;
+///
+/// assert!(same_type(x, y));
+/// assert!(!same_type(x, z));
+/// ```
+pub fn same_type(a: &dyn DynEq, b: &dyn DynEq) -> bool {
+    type_id_of(a) == type_id_of(b)
+}
+
+/// Returns `true` if the two trait objects were created from the same
+/// underlying concrete type.
+///
+/// This is the `DynOrd` counterpart of [`same_type`].
+///
+/// ```
+/// # use dyn_ord::{DynOrd, same_type_ord};
+/// let x: &dyn DynOrd = &1.5;
+/// let y: &dyn DynOrd = &f64::NAN;
+/// let z: &dyn DynOrd = &1.5_f32;
+///
+/// assert!(same_type_ord(x, y));
+/// assert!(!same_type_ord(x, z));
+/// ```
+pub fn same_type_ord(a: &dyn DynOrd, b: &dyn DynOrd) -> bool {
+    same_type(a, b)
+}
+
+/// Returns `true` if the two trait objects point to the same value.
+///
+/// Only the addresses and the underlying concrete types are compared,
+/// the vtable pointers are ignored. This makes the comparison O(1),
+/// which is useful e.g. for detecting clones of the same `Rc`.
+///
+/// If `ptr_eq(a, b)` holds, then `a == b` holds as well, provided that
+/// the `PartialEq` impl of the underlying type is reflexive. (It is not
+/// for floating-point NaN, for example.) The converse is not true:
+/// distinct values may well compare equal.
+///
+/// ```
+/// # use std::rc::Rc;
+/// # use dyn_ord::{DynEq, ptr_eq};
+/// let x: Rc<dyn DynEq> = Rc::new(42);
+/// let y = Rc::clone(&x);
+/// let z: Rc<dyn DynEq> = Rc::new(42);
+///
+/// assert!(ptr_eq(&*x, &*y));
+/// assert!(!ptr_eq(&*x, &*z));
+/// assert!(*x == *z);
+/// ```
+pub fn ptr_eq(a: &dyn DynEq, b: &dyn DynEq) -> bool {
+    let a_ptr = a as *const dyn DynEq as *const ();
+    let b_ptr = b as *const dyn DynEq as *const ();
+
+    a_ptr == b_ptr && same_type(a, b)
+}
+
+/// Returns `true` if the address of the value underlying `a` is less
+/// than that of `b`.
+///
+/// This is unrelated to the value-based ordering of `DynOrd`; it is an
+/// arbitrary but consistent order usable for canonicalizing e.g. pairs
+/// of graph nodes, as long as the values are not moved.
+///
+/// ```
+/// # use dyn_ord::{DynOrd, ptr_lt};
+/// let values = [1, 2];
+/// let x: &dyn DynOrd = &values[0];
+/// let y: &dyn DynOrd = &values[1];
+///
+/// assert!(ptr_lt(x, y));
+/// assert!(!ptr_lt(y, x));
+/// assert!(!ptr_lt(x, x));
+/// ```
+pub fn ptr_lt(a: &dyn DynOrd, b: &dyn DynOrd) -> bool {
+    let a_ptr = a as *const dyn DynOrd as *const ();
+    let b_ptr = b as *const dyn DynOrd as *const ();
+
+    a_ptr < b_ptr
+}
+
+/// Converts a `DynOrd` trait object into a `DynEq` trait object.
+///
+/// This allows passing `dyn DynOrd` values to functions that only
+/// require `dyn DynEq`. The result compares equal to other `DynEq`
+/// trait objects exactly when the underlying values are equal.
+///
+/// ```
+/// # use dyn_ord::{DynEq, DynOrd, as_dyn_eq};
+/// let x: &dyn DynOrd = &42;
+/// let y: &dyn DynEq = &42;
+///
+/// assert!(*as_dyn_eq(x) == *y);
+/// ```
+pub fn as_dyn_eq(x: &dyn DynOrd) -> &dyn DynEq {
+    x
+}
+
+/// Converts a mutable `DynOrd` trait object into a mutable `DynEq`
+/// trait object.
+///
+/// ```
+/// # use dyn_ord::{DynOrd, as_dyn_eq_mut};
+/// let mut x: Box<dyn DynOrd> = Box::new(42);
+///
+/// *as_dyn_eq_mut(&mut *x).downcast_mut::<i32>().unwrap() = 43;
+///
+/// assert_eq!(x.downcast_ref::<i32>(), Some(&43));
+/// ```
+pub fn as_dyn_eq_mut(x: &mut dyn DynOrd) -> &mut dyn DynEq {
+    x
+}
+
+/// Attempts to downcast a boxed trait object to a concrete type.
+///
+/// Returns the box with its type recovered if the underlying value is
+/// of type `T`, or the original box otherwise. This mirrors the
+/// `downcast` method of `Box<dyn Any>`, and it does not reallocate.
+///
+/// ```
+/// # use dyn_ord::{DynEq, downcast_box};
+/// let x: Box<dyn DynEq> = Box::new(String::from("qux"));
+///
+/// let x = match downcast_box::<i32>(x) {
+///     Ok(_) => unreachable!(),
+///     Err(x) => x,
+/// };
+/// let x: Box<String> = downcast_box(x).ok().unwrap();
+///
+/// assert_eq!(*x, "qux");
+/// ```
+pub fn downcast_box<T: Any + PartialEq>(b: Box<dyn DynEq>) -> Result<Box<T>, Box<dyn DynEq>> {
+    if b.is::<T>() {
+        let b: Box<dyn Any> = b;
+        Ok(b.downcast().unwrap_or_else(|_| unreachable!()))
+    } else {
+        Err(b)
+    }
+}
+
+/// Converts a boxed `DynEq` trait object into a boxed `Any` trait object.
+///
+/// The resulting box points to the same value, without reallocation,
+/// and it can be downcast or passed to APIs expecting `Box<dyn Any>`.
+///
+/// ```
+/// # use core::any::Any;
+/// # use dyn_ord::{DynEq, into_any_box, try_downcast_any_box};
+/// let x: Box<dyn DynEq> = Box::new(42);
+/// let x: Box<dyn Any> = into_any_box(x);
+///
+/// assert_eq!(x.downcast_ref::<i32>(), Some(&42));
+/// assert_eq!(try_downcast_any_box::<i32>(x).ok(), Some(42));
+/// ```
+pub fn into_any_box(b: Box<dyn DynEq + 'static>) -> Box<dyn Any> {
+    b
+}
+
+/// Converts a boxed `DynEq` trait object into a boxed `Any` trait object,
+/// like [`into_any_box`].
+///
+/// ```
+/// # use core::any::Any;
+/// # use dyn_ord::DynEq;
+/// let x: Box<dyn DynEq> = Box::new(42);
+/// let x: Box<dyn Any> = x.into();
+///
+/// assert_eq!(x.downcast_ref::<i32>(), Some(&42));
+/// ```
+impl From<Box<dyn DynEq>> for Box<dyn Any> {
+    fn from(b: Box<dyn DynEq>) -> Self {
+        b
+    }
+}
+
+/// Converts a boxed `DynOrd` trait object into a boxed `Any` trait object,
+/// without reallocation.
+///
+/// ```
+/// # use core::any::Any;
+/// # use dyn_ord::DynOrd;
+/// let x: Box<dyn DynOrd> = Box::new(String::from("qux"));
+/// let x: Box<dyn Any> = x.into();
+///
+/// assert_eq!(x.downcast_ref::<String>().map(String::as_str), Some("qux"));
+/// ```
+impl From<Box<dyn DynOrd>> for Box<dyn Any> {
+    fn from(b: Box<dyn DynOrd>) -> Self {
+        b
+    }
+}
+
+/// Attempts to unbox a value of type `T` from a `Box<dyn Any>`.
+///
+/// Returns the original box if the underlying value is not of type `T`.
+///
+/// ```
+/// # use core::any::Any;
+/// # use dyn_ord::{DynEq, into_any_box, try_downcast_any_box};
+/// let x: Box<dyn Any> = into_any_box(Box::new(String::from("qux")));
+/// let x = try_downcast_any_box::<u8>(x).unwrap_err();
+/// let y: Box<dyn DynEq> = Box::new(try_downcast_any_box::<String>(x).unwrap());
+///
+/// assert!(*y == *(Box::new(String::from("qux")) as Box<dyn DynEq>));
+/// ```
+pub fn try_downcast_any_box<T: Any + PartialEq>(b: Box<dyn Any>) -> Result<T, Box<dyn Any>> {
+    b.downcast().map(|b| *b)
+}
@@ -0,0 +1,302 @@
+//! Assertion macros for dynamically-typed values, and their runtime
+//! support. The functions in this module are not part of the public API.
+
+use core::fmt;
+use crate::{DynEq, DynOrd, type_id_of, type_name_of};
+
+/// Asserts that two `DynEq` values are equal.
+///
+/// The operands must be references coercible to `&dyn DynEq`. On
+/// failure, the panic message contains the type name and `TypeId` of
+/// both operands, and whether the failure is due to a type mismatch or
+/// due to different values. Like `assert_eq!`, this macro accepts an
+/// optional custom message with format arguments.
+///
+/// ```
+/// # use dyn_ord::{DynEq, assert_dyn_eq};
+/// let x: Box<dyn DynEq> = Box::new(42);
+///
+/// assert_dyn_eq!(&*x, &42);
+/// assert_dyn_eq!(&*x, &42, "x should be {}", 42);
+/// ```
+///
+/// ```should_panic
+/// # use dyn_ord::assert_dyn_eq;
+/// // panics with a type mismatch
+/// assert_dyn_eq!(&42, &42_u64);
+/// ```
+#[macro_export]
+macro_rules! assert_dyn_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match ($left, $right) {
+            (left, right) => {
+                let left: &dyn $crate::DynEq = left;
+                let right: &dyn $crate::DynEq = right;
+
+                if !(*left == *right) {
+                    $crate::macros::assert_dyn_eq_failed(true, left, right, None);
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match ($left, $right) {
+            (left, right) => {
+                let left: &dyn $crate::DynEq = left;
+                let right: &dyn $crate::DynEq = right;
+
+                if !(*left == *right) {
+                    $crate::macros::assert_dyn_eq_failed(
+                        true,
+                        left,
+                        right,
+                        Some(format_args!($($arg)+)),
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// Asserts that two `DynEq` values are not equal.
+///
+/// The operands must be references coercible to `&dyn DynEq`. On
+/// failure, the panic message contains the type name and `TypeId` of
+/// both operands. Like `assert_ne!`, this macro accepts an optional
+/// custom message with format arguments.
+///
+/// ```
+/// # use dyn_ord::assert_dyn_ne;
+/// assert_dyn_ne!(&42, &43);
+/// assert_dyn_ne!(&42, &42_u64, "different types are never equal");
+/// ```
+///
+/// ```should_panic
+/// # use dyn_ord::assert_dyn_ne;
+/// assert_dyn_ne!(&"qux", &"qux");
+/// ```
+#[macro_export]
+macro_rules! assert_dyn_ne {
+    ($left:expr, $right:expr $(,)?) => {
+        match ($left, $right) {
+            (left, right) => {
+                let left: &dyn $crate::DynEq = left;
+                let right: &dyn $crate::DynEq = right;
+
+                if *left == *right {
+                    $crate::macros::assert_dyn_eq_failed(false, left, right, None);
+                }
+            }
+        }
+    };
+    ($left:expr, $right:expr, $($arg:tt)+) => {
+        match ($left, $right) {
+            (left, right) => {
+                let left: &dyn $crate::DynEq = left;
+                let right: &dyn $crate::DynEq = right;
+
+                if *left == *right {
+                    $crate::macros::assert_dyn_eq_failed(
+                        false,
+                        left,
+                        right,
+                        Some(format_args!($($arg)+)),
+                    );
+                }
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __assert_dyn_ord {
+    ($op:tt, $left:expr, $right:expr $(,)?) => {
+        match ($left, $right) {
+            (left, right) => {
+                let left: &dyn $crate::DynOrd = left;
+                let right: &dyn $crate::DynOrd = right;
+
+                if !(*left $op *right) {
+                    $crate::macros::assert_dyn_ord_failed(stringify!($op), left, right, None);
+                }
+            }
+        }
+    };
+    ($op:tt, $left:expr, $right:expr, $($arg:tt)+) => {
+        match ($left, $right) {
+            (left, right) => {
+                let left: &dyn $crate::DynOrd = left;
+                let right: &dyn $crate::DynOrd = right;
+
+                if !(*left $op *right) {
+                    $crate::macros::assert_dyn_ord_failed(
+                        stringify!($op),
+                        left,
+                        right,
+                        Some(format_args!($($arg)+)),
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// Asserts that a `DynOrd` value is less than another.
+///
+/// The operands must be references coercible to `&dyn DynOrd`. On
+/// failure, the panic message contains the type name and `TypeId` of
+/// both operands, and the result of `partial_cmp`, which is `None` for
+/// values of different types. Like `assert!`, this macro accepts an
+/// optional custom message with format arguments.
+///
+/// ```
+/// # use dyn_ord::assert_dyn_lt;
+/// assert_dyn_lt!(&1, &2);
+/// assert_dyn_lt!(&"baz", &"qux", "strings are compared lexicographically");
+/// ```
+///
+/// ```should_panic
+/// # use dyn_ord::assert_dyn_lt;
+/// assert_dyn_lt!(&2, &2);
+/// ```
+#[macro_export]
+macro_rules! assert_dyn_lt {
+    ($($arg:tt)+) => {
+        $crate::__assert_dyn_ord!(<, $($arg)+)
+    };
+}
+
+/// Asserts that a `DynOrd` value is greater than another.
+///
+/// See [`assert_dyn_lt!`] for details.
+///
+/// ```
+/// # use dyn_ord::assert_dyn_gt;
+/// assert_dyn_gt!(&2.5, &1.0);
+/// assert_dyn_gt!(&'y', &'x', "{} comes after {}", 'y', 'x');
+/// ```
+///
+/// ```should_panic
+/// # use dyn_ord::assert_dyn_gt;
+/// // panics because the values are of different types
+/// assert_dyn_gt!(&1, &"qux");
+/// ```
+#[macro_export]
+macro_rules! assert_dyn_gt {
+    ($($arg:tt)+) => {
+        $crate::__assert_dyn_ord!(>, $($arg)+)
+    };
+}
+
+/// Asserts that a `DynOrd` value is less than or equal to another.
+///
+/// See [`assert_dyn_lt!`] for details.
+///
+/// ```
+/// # use dyn_ord::assert_dyn_le;
+/// assert_dyn_le!(&1, &1);
+/// assert_dyn_le!(&1, &2, "one is not greater than two");
+/// ```
+///
+/// ```should_panic
+/// # use dyn_ord::assert_dyn_le;
+/// // panics because NaN is not comparable
+/// assert_dyn_le!(&f64::NAN, &1.0);
+/// ```
+#[macro_export]
+macro_rules! assert_dyn_le {
+    ($($arg:tt)+) => {
+        $crate::__assert_dyn_ord!(<=, $($arg)+)
+    };
+}
+
+/// Asserts that a `DynOrd` value is greater than or equal to another.
+///
+/// See [`assert_dyn_lt!`] for details.
+///
+/// ```
+/// # use dyn_ord::assert_dyn_ge;
+/// assert_dyn_ge!(&2, &2);
+/// assert_dyn_ge!(&3, &2, "three is not less than two");
+/// ```
+///
+/// ```should_panic
+/// # use dyn_ord::assert_dyn_ge;
+/// assert_dyn_ge!(&1, &2);
+/// ```
+#[macro_export]
+macro_rules! assert_dyn_ge {
+    ($($arg:tt)+) => {
+        $crate::__assert_dyn_ord!(>=, $($arg)+)
+    };
+}
+
+/// Runtime support for `assert_dyn_eq!` and `assert_dyn_ne!`.
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_dyn_eq_failed(
+    expected_eq: bool,
+    left: &dyn DynEq,
+    right: &dyn DynEq,
+    args: Option<fmt::Arguments<'_>>,
+) -> ! {
+    let op = if expected_eq { "==" } else { "!=" };
+    let reason = match left.dyn_eq_typed(right) {
+        None => "type mismatch",
+        Some(false) => "values differ",
+        Some(true) => "values are equal",
+    };
+
+    assert_failed(op, left, right, format_args!("{}", reason), args)
+}
+
+/// Runtime support for the ordering assertion macros.
+#[doc(hidden)]
+#[track_caller]
+pub fn assert_dyn_ord_failed(
+    op: &str,
+    left: &dyn DynOrd,
+    right: &dyn DynOrd,
+    args: Option<fmt::Arguments<'_>>,
+) -> ! {
+    let cmp = left.partial_cmp(right);
+
+    assert_failed(op, left, right, format_args!("`left.partial_cmp(right)` is {:?}", cmp), args)
+}
+
+#[track_caller]
+fn assert_failed(
+    op: &str,
+    left: &dyn DynEq,
+    right: &dyn DynEq,
+    reason: fmt::Arguments<'_>,
+    args: Option<fmt::Arguments<'_>>,
+) -> ! {
+    panic!(
+        "assertion `left {op} right` failed{sep}{msg}\n  \
+         left: {left_name} ({left_id:?})\n \
+         right: {right_name} ({right_id:?})\n\
+         reason: {reason}",
+        op = op,
+        sep = if args.is_some() { ": " } else { "" },
+        msg = OptionalArgs(args),
+        left_name = type_name_of(left),
+        left_id = type_id_of(left),
+        right_name = type_name_of(right),
+        right_id = type_id_of(right),
+        reason = reason,
+    )
+}
+
+/// Formats an optional custom assertion message.
+struct OptionalArgs<'a>(Option<fmt::Arguments<'a>>);
+
+impl fmt::Display for OptionalArgs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(args) => fmt::Display::fmt(&args, f),
+            None => Ok(()),
+        }
+    }
+}
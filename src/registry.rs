@@ -0,0 +1,96 @@
+//! User-defined equality rules across types.
+
+use core::any::{Any, TypeId};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use crate::DynEq;
+
+/// A type-erased equality rule, taking values of the types in its key.
+type Rule = Box<dyn Fn(&dyn Any, &dyn Any) -> bool>;
+
+/// A set of user-defined rules for comparing values of different types.
+///
+/// By default, values of different types are never equal according to
+/// `DynEq`. A registry allows overriding this for specific pairs of types,
+/// e.g. for comparing `&str` with `String`, or integers of different
+/// widths. Values of types without a registered rule are compared using
+/// `DynEq` as usual.
+///
+/// Rules are symmetric: a rule registered for `(A, B)` is also used for
+/// comparing a `B` with an `A`, with the arguments swapped.
+///
+/// ```
+/// # use dyn_ord::{DynEq, DynEqRegistry};
+/// let mut registry = DynEqRegistry::new();
+///
+/// registry.register_cross_type_eq(|a: &&str, b: &String| *a == b);
+/// registry.register_cross_type_eq(|a: &u32, b: &i32| i64::from(*a) == i64::from(*b));
+///
+/// assert!(registry.dyn_eq(&"qux", &String::from("qux")));
+/// assert!(registry.dyn_eq(&String::from("qux"), &"qux"));
+/// assert!(registry.dyn_eq(&42_i32, &42_u32));
+/// assert!(!registry.dyn_eq(&-1_i32, &u32::MAX));
+///
+/// // no rule is registered for these types, so they are compared as usual
+/// assert!(registry.dyn_eq(&42_u64, &42_u64));
+/// assert!(!registry.dyn_eq(&42_u64, &42_u32));
+/// ```
+#[derive(Default)]
+pub struct DynEqRegistry {
+    rules: BTreeMap<(TypeId, TypeId), Rule>,
+}
+
+impl DynEqRegistry {
+    /// Creates a registry without any rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a rule for comparing values of type `A` with values of
+    /// type `B`, replacing the rule previously registered for the same
+    /// pair of types, if any (in either order).
+    ///
+    /// If `A` and `B` are the same type, the rule overrides the `DynEq`
+    /// impl of the type when comparing via this registry.
+    pub fn register_cross_type_eq<A, B, F>(&mut self, f: F)
+    where
+        A: Any,
+        B: Any,
+        F: Fn(&A, &B) -> bool + 'static,
+    {
+        let key = (TypeId::of::<A>(), TypeId::of::<B>());
+        let rule: Rule = Box::new(move |a, b| {
+            let a = a.downcast_ref().unwrap_or_else(|| unreachable!());
+            let b = b.downcast_ref().unwrap_or_else(|| unreachable!());
+            f(a, b)
+        });
+
+        self.rules.remove(&(key.1, key.0));
+        self.rules.insert(key, rule);
+    }
+
+    /// Compares two values using the rule registered for their types, or
+    /// using `DynEq` if there is no such rule.
+    pub fn dyn_eq(&self, a: &dyn DynEq, b: &dyn DynEq) -> bool {
+        let a_type = a.as_any().type_id();
+        let b_type = b.as_any().type_id();
+
+        if let Some(rule) = self.rules.get(&(a_type, b_type)) {
+            rule(a.as_any(), b.as_any())
+        } else if let Some(rule) = self.rules.get(&(b_type, a_type)) {
+            rule(b.as_any(), a.as_any())
+        } else {
+            a.dyn_eq(b)
+        }
+    }
+
+    /// Returns the number of registered rules.
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns `true` if no rules are registered.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
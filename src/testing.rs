@@ -0,0 +1,158 @@
+//! Checks for the laws that `DynEq` and `DynOrd` impls must uphold.
+//!
+//! These are meant to be called from tests, with as many inputs as
+//! possible, e.g. generated by a property testing framework. Each check
+//! panics with a description of the violated law if it fails.
+//!
+//! This module is only available if the `testing` feature is enabled.
+
+use core::cmp::Ordering;
+use crate::{DynEq, DynOrd, type_name_of};
+
+/// Checks that `a` is equal to itself.
+///
+/// ```
+/// # use dyn_ord::testing::check_dyn_eq_reflexive;
+/// check_dyn_eq_reflexive(&42);
+/// check_dyn_eq_reflexive(&String::from("qux"));
+/// ```
+///
+/// ```should_panic
+/// # use dyn_ord::testing::check_dyn_eq_reflexive;
+/// check_dyn_eq_reflexive(&f64::NAN);
+/// ```
+#[track_caller]
+pub fn check_dyn_eq_reflexive<T: DynEq + ?Sized>(a: &T) {
+    assert!(
+        a.dyn_eq(a.as_dyn_eq()),
+        "`dyn_eq` is not reflexive for a value of type `{}`",
+        type_name_of(a.as_dyn_eq()),
+    );
+}
+
+/// Checks that `a` is equal to `b` if and only if `b` is equal to `a`.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::testing::check_dyn_eq_symmetric;
+/// check_dyn_eq_symmetric(&1, &2);
+/// check_dyn_eq_symmetric::<dyn DynEq>(&1, &"qux");
+/// ```
+#[track_caller]
+pub fn check_dyn_eq_symmetric<T: DynEq + ?Sized>(a: &T, b: &T) {
+    assert_eq!(
+        a.dyn_eq(b.as_dyn_eq()),
+        b.dyn_eq(a.as_dyn_eq()),
+        "`dyn_eq` is not symmetric for values of types `{}` and `{}`",
+        type_name_of(a.as_dyn_eq()),
+        type_name_of(b.as_dyn_eq()),
+    );
+}
+
+/// Checks that `a` is equal to `c` if `a` is equal to `b` and `b` is
+/// equal to `c`.
+///
+/// ```
+/// # use dyn_ord::testing::check_dyn_eq_transitive;
+/// check_dyn_eq_transitive(&1, &1, &1);
+/// check_dyn_eq_transitive(&1, &1, &2);
+/// ```
+#[track_caller]
+pub fn check_dyn_eq_transitive<T: DynEq + ?Sized>(a: &T, b: &T, c: &T) {
+    if a.dyn_eq(b.as_dyn_eq()) && b.dyn_eq(c.as_dyn_eq()) {
+        assert!(
+            a.dyn_eq(c.as_dyn_eq()),
+            "`dyn_eq` is not transitive for values of types `{}`, `{}` and `{}`",
+            type_name_of(a.as_dyn_eq()),
+            type_name_of(b.as_dyn_eq()),
+            type_name_of(c.as_dyn_eq()),
+        );
+    }
+}
+
+/// Checks that `dyn_eq` agrees with `PartialEq::eq` for `a` and `b`.
+///
+/// ```
+/// # use dyn_ord::testing::check_dyn_eq_consistent_with_partial_eq;
+/// check_dyn_eq_consistent_with_partial_eq(&1.5, &1.5);
+/// check_dyn_eq_consistent_with_partial_eq(&f64::NAN, &f64::NAN);
+/// ```
+#[track_caller]
+pub fn check_dyn_eq_consistent_with_partial_eq<T: DynEq + PartialEq>(a: &T, b: &T) {
+    assert_eq!(
+        a.dyn_eq(b.as_dyn_eq()),
+        a == b,
+        "`dyn_eq` is inconsistent with `PartialEq` for values of type `{}`",
+        type_name_of(a.as_dyn_eq()),
+    );
+}
+
+/// Checks that `a` is less than `b` if and only if `b` is greater than `a`,
+/// and that they are equal or incomparable in both directions otherwise.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::testing::check_dyn_ord_antisymmetric;
+/// check_dyn_ord_antisymmetric(&1, &2);
+/// check_dyn_ord_antisymmetric(&f64::NAN, &1.0);
+/// check_dyn_ord_antisymmetric::<dyn DynOrd>(&1, &"qux");
+/// ```
+#[track_caller]
+pub fn check_dyn_ord_antisymmetric<T: DynOrd + ?Sized>(a: &T, b: &T) {
+    assert_eq!(
+        a.dyn_ord(b.as_dyn_ord()),
+        b.dyn_ord(a.as_dyn_ord()).map(Ordering::reverse),
+        "`dyn_ord` is not antisymmetric for values of types `{}` and `{}`",
+        type_name_of(a.as_dyn_eq()),
+        type_name_of(b.as_dyn_eq()),
+    );
+}
+
+/// Checks that the ordering of `a` and `c` follows from the orderings of
+/// `a` and `b`, and of `b` and `c`, if it can be inferred from them.
+///
+/// ```
+/// # use dyn_ord::testing::check_dyn_ord_transitive;
+/// check_dyn_ord_transitive(&1, &2, &3);
+/// check_dyn_ord_transitive(&1, &1, &0);
+/// check_dyn_ord_transitive(&3, &1, &2);
+/// ```
+#[track_caller]
+pub fn check_dyn_ord_transitive<T: DynOrd + ?Sized>(a: &T, b: &T, c: &T) {
+    let expected = match (a.dyn_ord(b.as_dyn_ord()), b.dyn_ord(c.as_dyn_ord())) {
+        (Some(Ordering::Equal), bc) => bc,
+        (ab, Some(Ordering::Equal)) => ab,
+        (Some(ab), Some(bc)) if ab == bc => Some(ab),
+        _ => return,
+    };
+
+    assert_eq!(
+        a.dyn_ord(c.as_dyn_ord()),
+        expected,
+        "`dyn_ord` is not transitive for values of types `{}`, `{}` and `{}`",
+        type_name_of(a.as_dyn_eq()),
+        type_name_of(b.as_dyn_eq()),
+        type_name_of(c.as_dyn_eq()),
+    );
+}
+
+/// Checks that `dyn_ord` returns `Some(Ordering::Equal)` if and only if
+/// `dyn_eq` returns `true` for `a` and `b`.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::testing::check_dyn_ord_consistent_with_dyn_eq;
+/// check_dyn_ord_consistent_with_dyn_eq(&1, &1);
+/// check_dyn_ord_consistent_with_dyn_eq(&f64::NAN, &f64::NAN);
+/// check_dyn_ord_consistent_with_dyn_eq::<dyn DynOrd>(&1, &1_u8);
+/// ```
+#[track_caller]
+pub fn check_dyn_ord_consistent_with_dyn_eq<T: DynOrd + ?Sized>(a: &T, b: &T) {
+    assert_eq!(
+        a.dyn_ord(b.as_dyn_ord()) == Some(Ordering::Equal),
+        a.dyn_eq(b.as_dyn_eq()),
+        "`dyn_ord` is inconsistent with `dyn_eq` for values of types `{}` and `{}`",
+        type_name_of(a.as_dyn_eq()),
+        type_name_of(b.as_dyn_eq()),
+    );
+}
@@ -0,0 +1,1811 @@
+//! Algorithms over collections of dynamically-typed values.
+//!
+//! Functions that need a total order over heterogeneous values, such
+//! as [`sort_dyn`], group values by type: values of the same type are
+//! compared using `DynOrd`, while values of different types are ordered
+//! by their `TypeId`. The relative order of the types is arbitrary, but
+//! it is consistent within a single run of the program. Values that are
+//! incomparable with themselves, such as floating-point NaNs, come before
+//! all other values of their type, and they are equal to each other.
+
+use core::any::{Any, TypeId};
+use core::cmp::Ordering;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use alloc::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::collections::{HashMap, HashSet};
+#[cfg(feature = "std")]
+use crate::DynHash;
+use crate::{DynEq, DynOrd, same_type, same_type_ord, type_id_of, type_name_of, total_cmp};
+
+/// Sorts a slice of heterogeneous values, grouping them by type.
+///
+/// This sort is stable. Values that are incomparable with themselves
+/// (e.g. floating-point NaNs) are placed before all other values of the
+/// same type.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::sort_dyn;
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(3),
+///     Box::new("qux"),
+///     Box::new(1),
+///     Box::new("baz"),
+///     Box::new(2),
+/// ];
+///
+/// sort_dyn(&mut values);
+///
+/// let ints: Vec<i32> = values.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+/// let strs: Vec<&str> = values.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+///
+/// assert_eq!(ints, [1, 2, 3]);
+/// assert_eq!(strs, ["baz", "qux"]);
+///
+/// // values of the same type are contiguous
+/// let type_changes = values.windows(2).filter(|w| w[0].is::<i32>() != w[1].is::<i32>()).count();
+/// assert_eq!(type_changes, 1);
+/// ```
+///
+/// Wrapper types are distinct from the types they wrap, so they form a
+/// separate group with their own order:
+///
+/// ```
+/// # use core::cmp::Reverse;
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::sort_dyn;
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(1),
+///     Box::new(Reverse(1)),
+///     Box::new(3),
+///     Box::new(Reverse(3)),
+///     Box::new(2),
+/// ];
+///
+/// sort_dyn(&mut values);
+///
+/// let ints: Vec<i32> = values.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+/// let reversed: Vec<i32> = values
+///     .iter()
+///     .filter_map(|x| x.downcast_ref::<Reverse<i32>>())
+///     .map(|x| x.0)
+///     .collect();
+///
+/// assert_eq!(ints, [1, 2, 3]);
+/// assert_eq!(reversed, [3, 1]);
+/// ```
+///
+/// NaNs don't disturb the order of the other values:
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::sort_dyn;
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(3.0),
+///     Box::new(f64::NAN),
+///     Box::new(1.0),
+///     Box::new(f64::NAN),
+///     Box::new(2.0),
+/// ];
+///
+/// sort_dyn(&mut values);
+///
+/// let floats: Vec<f64> = values.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+///
+/// assert!(floats[..2].iter().all(|x| x.is_nan()));
+/// assert_eq!(floats[2..], [1.0, 2.0, 3.0]);
+/// ```
+pub fn sort_dyn(slice: &mut [Box<dyn DynOrd>]) {
+    slice.sort_by(|a, b| total_cmp(&**a, &**b));
+}
+
+/// Sorts a slice of heterogeneous values, grouping them by type.
+///
+/// This sort is unstable, but it may be faster than [`sort_dyn`]. Like
+/// `sort_dyn`, it places NaNs before all other values of the same type.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::sort_dyn_unstable;
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new('b'),
+///     Box::new(2.5),
+///     Box::new('a'),
+///     Box::new(-1.0),
+/// ];
+///
+/// sort_dyn_unstable(&mut values);
+///
+/// let chars: Vec<char> = values.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+/// let floats: Vec<f64> = values.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+///
+/// assert_eq!(chars, ['a', 'b']);
+/// assert_eq!(floats, [-1.0, 2.5]);
+/// ```
+pub fn sort_dyn_unstable(slice: &mut [Box<dyn DynOrd>]) {
+    slice.sort_unstable_by(|a, b| total_cmp(&**a, &**b));
+}
+
+/// Sorts a slice of heterogeneous values using a custom comparator.
+///
+/// The comparator must define a total order over all values in the
+/// slice, including values of different types. This sort is stable.
+///
+/// ```
+/// # use core::cmp::Ordering;
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::sort_dyn_by;
+/// // strings first, then integers, each in descending order
+/// fn priority(x: &dyn DynOrd) -> u8 {
+///     if x.is::<&str>() { 0 } else { 1 }
+/// }
+///
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(1),
+///     Box::new("baz"),
+///     Box::new(3),
+///     Box::new("qux"),
+/// ];
+///
+/// sort_dyn_by(&mut values, |a, b| {
+///     priority(a).cmp(&priority(b)).then_with(|| b.partial_cmp(a).unwrap_or(Ordering::Equal))
+/// });
+///
+/// assert_eq!(values[0].downcast_ref::<&str>(), Some(&"qux"));
+/// assert_eq!(values[1].downcast_ref::<&str>(), Some(&"baz"));
+/// assert_eq!(values[2].downcast_ref::<i32>(), Some(&3));
+/// assert_eq!(values[3].downcast_ref::<i32>(), Some(&1));
+/// ```
+pub fn sort_dyn_by<F>(slice: &mut [Box<dyn DynOrd>], mut cmp: F)
+where
+    F: FnMut(&dyn DynOrd, &dyn DynOrd) -> Ordering,
+{
+    slice.sort_by(|a, b| cmp(&**a, &**b));
+}
+
+/// Sorts a slice of heterogeneous values using a custom comparator.
+///
+/// This is the unstable counterpart of [`sort_dyn_by`].
+///
+/// ```
+/// # use dyn_ord::{DynOrd, type_name_of};
+/// # use dyn_ord::util::sort_dyn_unstable_by;
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(2_u8),
+///     Box::new('x'),
+///     Box::new(1_u8),
+/// ];
+///
+/// // order types by name, and values by their natural order
+/// sort_dyn_unstable_by(&mut values, |a, b| {
+///     type_name_of(a).cmp(type_name_of(b)).then_with(|| a.partial_cmp(b).unwrap())
+/// });
+///
+/// assert_eq!(values[0].downcast_ref::<char>(), Some(&'x'));
+/// assert_eq!(values[1].downcast_ref::<u8>(), Some(&1));
+/// assert_eq!(values[2].downcast_ref::<u8>(), Some(&2));
+/// ```
+pub fn sort_dyn_unstable_by<F>(slice: &mut [Box<dyn DynOrd>], mut cmp: F)
+where
+    F: FnMut(&dyn DynOrd, &dyn DynOrd) -> Ordering,
+{
+    slice.sort_unstable_by(|a, b| cmp(&**a, &**b));
+}
+
+/// A policy for ordering values of different types relative to each other.
+///
+/// Sorting functions such as [`sort_dyn_with_policy`] consult the policy
+/// first, and only compare values using `DynOrd` if the policy considers
+/// their types equal. A policy should therefore define a total order over
+/// types, and it should only return `Ordering::Equal` for values of the
+/// same type; otherwise values of different types may be interleaved.
+///
+/// The policy receives the values themselves rather than their `TypeId`s,
+/// so that it can inspect their types in any way it sees fit, e.g. using
+/// `is()` or [`type_name_of`](crate::type_name_of()).
+///
+/// ```
+/// # use core::cmp::Ordering;
+/// # use dyn_ord::{DynOrd, type_id_of};
+/// # use dyn_ord::util::{CrossTypeOrdPolicy, sort_dyn_with_policy};
+/// /// Orders errors before all other values.
+/// struct ErrorsFirst;
+///
+/// impl CrossTypeOrdPolicy for ErrorsFirst {
+///     fn compare_types(&self, a: &dyn DynOrd, b: &dyn DynOrd) -> Ordering {
+///         let a_is_value = !a.is::<std::io::ErrorKind>();
+///         let b_is_value = !b.is::<std::io::ErrorKind>();
+///
+///         a_is_value.cmp(&b_is_value).then_with(|| type_id_of(a).cmp(&type_id_of(b)))
+///     }
+/// }
+///
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(42),
+///     Box::new(std::io::ErrorKind::NotFound),
+///     Box::new("qux"),
+/// ];
+///
+/// sort_dyn_with_policy(&mut values, &ErrorsFirst);
+///
+/// assert!(values[0].is::<std::io::ErrorKind>());
+/// ```
+pub trait CrossTypeOrdPolicy: Send + Sync + 'static {
+    /// Compares the types of two values, disregarding the values themselves.
+    fn compare_types(&self, a: &dyn DynOrd, b: &dyn DynOrd) -> Ordering;
+}
+
+/// Orders types by their `TypeId`.
+///
+/// This is the policy used by [`sort_dyn`] and the other functions in this
+/// module that don't take a policy explicitly. The resulting order of the
+/// types is arbitrary, but consistent within a single run of the program.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub struct TypeIdPolicy;
+
+impl CrossTypeOrdPolicy for TypeIdPolicy {
+    fn compare_types(&self, a: &dyn DynOrd, b: &dyn DynOrd) -> Ordering {
+        type_id_of(a).cmp(&type_id_of(b))
+    }
+}
+
+/// Orders types alphabetically by their name, as returned by
+/// [`type_name_of`](crate::type_name_of()).
+///
+/// Unlike the order of [`TypeIdPolicy`], this order is predictable, and
+/// it doesn't change when the program is recompiled, which makes it
+/// useful for deterministic output, e.g. in tests or debug output. Type
+/// names are not guaranteed to be unique, so types with the same name
+/// are ordered by their `TypeId`. Since type names may change across
+/// compiler versions, the resulting order should not be relied upon for
+/// anything but presentation.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{TypeNameOrdPolicy, sort_dyn_with_policy};
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(2_u8),
+///     Box::new(1_i64),
+///     Box::new(1_u8),
+///     Box::new('x'),
+///     Box::new(true),
+/// ];
+///
+/// sort_dyn_with_policy(&mut values, &TypeNameOrdPolicy);
+///
+/// assert_eq!(values[0].downcast_ref::<bool>(), Some(&true));
+/// assert_eq!(values[1].downcast_ref::<char>(), Some(&'x'));
+/// assert_eq!(values[2].downcast_ref::<i64>(), Some(&1));
+/// assert_eq!(values[3].downcast_ref::<u8>(), Some(&1));
+/// assert_eq!(values[4].downcast_ref::<u8>(), Some(&2));
+/// ```
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, Hash)]
+pub struct TypeNameOrdPolicy;
+
+impl CrossTypeOrdPolicy for TypeNameOrdPolicy {
+    fn compare_types(&self, a: &dyn DynOrd, b: &dyn DynOrd) -> Ordering {
+        type_name_of(a)
+            .cmp(type_name_of(b))
+            .then_with(|| type_id_of(a).cmp(&type_id_of(b)))
+    }
+}
+
+/// Compares values according to the type order imposed by `policy`, then
+/// in the same order as [`sort_dyn`].
+fn policy_cmp(policy: &dyn CrossTypeOrdPolicy, a: &dyn DynOrd, b: &dyn DynOrd) -> Ordering {
+    policy.compare_types(a, b).then_with(|| total_cmp(a, b))
+}
+
+/// Sorts a slice of heterogeneous values, grouping them by type, and
+/// ordering the groups according to `policy`.
+///
+/// This sort is stable. Values of the same type are ordered as by
+/// [`sort_dyn`], so values that are incomparable with themselves (e.g.
+/// floating-point NaNs) are placed before all other values of the type.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{TypeIdPolicy, sort_dyn, sort_dyn_with_policy};
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![Box::new(2), Box::new("qux"), Box::new(1)];
+/// let mut expected: Vec<Box<dyn DynOrd>> = vec![Box::new(2), Box::new("qux"), Box::new(1)];
+///
+/// sort_dyn_with_policy(&mut values, &TypeIdPolicy);
+/// sort_dyn(&mut expected);
+///
+/// assert!(values.iter().zip(&expected).all(|(x, y)| **x == **y));
+/// ```
+pub fn sort_dyn_with_policy(slice: &mut [Box<dyn DynOrd>], policy: &dyn CrossTypeOrdPolicy) {
+    slice.sort_by(|a, b| policy_cmp(policy, &**a, &**b));
+}
+
+/// Sorts a slice of heterogeneous values, grouping them by type, and
+/// ordering the groups according to `policy`.
+///
+/// This is the unstable counterpart of [`sort_dyn_with_policy`].
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{TypeNameOrdPolicy, sort_dyn_unstable_with_policy};
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![Box::new(2_u8), Box::new('x'), Box::new(1_u8)];
+///
+/// sort_dyn_unstable_with_policy(&mut values, &TypeNameOrdPolicy);
+///
+/// assert_eq!(values[0].downcast_ref::<char>(), Some(&'x'));
+/// assert_eq!(values[1].downcast_ref::<u8>(), Some(&1));
+/// assert_eq!(values[2].downcast_ref::<u8>(), Some(&2));
+/// ```
+pub fn sort_dyn_unstable_with_policy(slice: &mut [Box<dyn DynOrd>], policy: &dyn CrossTypeOrdPolicy) {
+    slice.sort_unstable_by(|a, b| policy_cmp(policy, &**a, &**b));
+}
+
+/// Sorts a slice of heterogeneous values by a key extracted from each
+/// value.
+///
+/// Since all keys are of the same type `K`, this avoids comparing values
+/// of different types altogether. This sort is stable.
+///
+/// ```
+/// # use dyn_ord::{DynOrd, type_name_of};
+/// # use dyn_ord::util::sort_dyn_by_key;
+/// fn priority(x: &dyn DynOrd) -> i64 {
+///     x.downcast_ref::<i64>()
+///         .copied()
+///         .or_else(|| x.downcast_ref::<&str>().map(|s| s.len() as i64))
+///         .unwrap_or(i64::MAX)
+/// }
+///
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new('x'),
+///     Box::new(5_i64),
+///     Box::new("qux"),
+///     Box::new(-2_i64),
+/// ];
+///
+/// sort_dyn_by_key(&mut values, priority);
+///
+/// let names: Vec<&str> = values.iter().map(|x| type_name_of(&**x)).collect();
+///
+/// assert_eq!(names, ["i64", "&str", "i64", "char"]);
+/// ```
+///
+/// Elements with equal keys keep their original relative order:
+///
+/// ```
+/// # use dyn_ord::{DynOrd, type_name_of};
+/// # use dyn_ord::util::sort_dyn_by_key;
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new("qux"),
+///     Box::new(2),
+///     Box::new("baz"),
+///     Box::new(1),
+/// ];
+///
+/// // group by type name, keeping the insertion order within groups
+/// sort_dyn_by_key(&mut values, |x| type_name_of(x));
+///
+/// assert_eq!(values[0].downcast_ref::<&str>(), Some(&"qux"));
+/// assert_eq!(values[1].downcast_ref::<&str>(), Some(&"baz"));
+/// assert_eq!(values[2].downcast_ref::<i32>(), Some(&2));
+/// assert_eq!(values[3].downcast_ref::<i32>(), Some(&1));
+/// ```
+pub fn sort_dyn_by_key<K, F>(slice: &mut [Box<dyn DynOrd>], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&dyn DynOrd) -> K,
+{
+    slice.sort_by_key(|x| key(&**x));
+}
+
+/// Sorts a slice of heterogeneous values by a key extracted from each
+/// value.
+///
+/// This is the unstable counterpart of [`sort_dyn_by_key`].
+///
+/// ```
+/// # use dyn_ord::{DynOrd, type_name_of};
+/// # use dyn_ord::util::sort_dyn_by_key_unstable;
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(String::from("qux")),
+///     Box::new(1_u8),
+///     Box::new(true),
+/// ];
+///
+/// sort_dyn_by_key_unstable(&mut values, |x| type_name_of(x).len());
+///
+/// assert!(values[0].is::<u8>());
+/// assert!(values[1].is::<bool>());
+/// assert!(values[2].is::<String>());
+/// ```
+pub fn sort_dyn_by_key_unstable<K, F>(slice: &mut [Box<dyn DynOrd>], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&dyn DynOrd) -> K,
+{
+    slice.sort_unstable_by_key(|x| key(&**x));
+}
+
+/// Binary searches a slice sorted by [`sort_dyn`] for the given key.
+///
+/// Returns `Ok` with the index of the first element equal to `key`,
+/// or `Err` with the index where `key` could be inserted while keeping
+/// the slice sorted. Values are compared using the same type-grouped
+/// order as `sort_dyn`, so searching for a NaN finds the first NaN of
+/// the same type, even though NaNs are not equal to each other.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn, dyn_binary_search};
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(3),
+///     Box::new("qux"),
+///     Box::new(1),
+///     Box::new(3),
+///     Box::new("baz"),
+/// ];
+///
+/// sort_dyn(&mut values);
+///
+/// let i = dyn_binary_search(&values, &3).unwrap();
+/// assert_eq!(values[i].downcast_ref::<i32>(), Some(&3));
+/// assert!(i == 0 || *values[i - 1] != *values[i]);
+///
+/// let j = dyn_binary_search(&values, &"bar").unwrap_err();
+/// values.insert(j, Box::new("bar"));
+/// assert_eq!(dyn_binary_search(&values, &"bar"), Ok(j));
+///
+/// assert!(dyn_binary_search(&values, &'x').is_err());
+/// ```
+pub fn dyn_binary_search(slice: &[Box<dyn DynOrd>], key: &dyn DynOrd) -> Result<usize, usize> {
+    dyn_binary_search_by(slice, |x| total_cmp(x, key))
+}
+
+/// Binary searches a sorted slice of heterogeneous values using a
+/// custom comparator.
+///
+/// The comparator should return the order of its argument relative to
+/// the target, and it must be consistent with the order of the slice.
+/// Returns `Ok` with the index of the first element for which it returns
+/// `Ordering::Equal`, or `Err` with the index where a matching element
+/// could be inserted while keeping the slice sorted.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn_by_key, dyn_binary_search_by};
+/// fn len(x: &dyn DynOrd) -> usize {
+///     x.downcast_ref::<&str>().map_or(0, |s| s.len())
+/// }
+///
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new("quux"),
+///     Box::new("q"),
+///     Box::new("qux"),
+///     Box::new("baz"),
+/// ];
+///
+/// sort_dyn_by_key(&mut values, len);
+///
+/// assert_eq!(dyn_binary_search_by(&values, |x| len(x).cmp(&3)), Ok(1));
+/// assert_eq!(dyn_binary_search_by(&values, |x| len(x).cmp(&2)), Err(1));
+/// ```
+pub fn dyn_binary_search_by<F>(slice: &[Box<dyn DynOrd>], mut f: F) -> Result<usize, usize>
+where
+    F: FnMut(&dyn DynOrd) -> Ordering,
+{
+    let index = slice.partition_point(|x| f(&**x) == Ordering::Less);
+
+    match slice.get(index) {
+        Some(x) if f(&**x) == Ordering::Equal => Ok(index),
+        _ => Err(index),
+    }
+}
+
+/// Returns the sub-slice of a slice sorted by [`sort_dyn`] containing the
+/// elements `x` with `lo <= x <= hi`.
+///
+/// Values are compared using the same type-grouped order as `sort_dyn`,
+/// so the result only contains elements of the same type as the bounds.
+/// It is empty if `lo` and `hi` are of different types, or if `lo > hi`.
+/// A NaN bound is less than all other values of its type, as in the
+/// order of `sort_dyn`.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn, dyn_range};
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(5),
+///     Box::new("qux"),
+///     Box::new(1),
+///     Box::new(3),
+///     Box::new(4),
+/// ];
+///
+/// sort_dyn(&mut values);
+///
+/// let range: Vec<i32> = dyn_range(&values, &2, &4)
+///     .iter()
+///     .filter_map(|x| x.downcast_ref().copied())
+///     .collect();
+///
+/// assert_eq!(range, [3, 4]);
+/// assert!(dyn_range(&values, &4, &2).is_empty());
+/// assert!(dyn_range(&values, &2, &"quz").is_empty());
+/// assert_eq!(dyn_range(&values, &"a", &"z").len(), 1);
+/// ```
+pub fn dyn_range<'a>(slice: &'a [Box<dyn DynOrd>], lo: &dyn DynOrd, hi: &dyn DynOrd) -> &'a [Box<dyn DynOrd>] {
+    if !same_type_ord(lo, hi) || total_cmp(lo, hi) == Ordering::Greater {
+        return &[];
+    }
+
+    let start = slice.partition_point(|x| total_cmp(&**x, lo) == Ordering::Less);
+    let end = slice.partition_point(|x| total_cmp(&**x, hi) != Ordering::Greater);
+
+    &slice[start..end]
+}
+
+/// Merges two slices sorted by [`sort_dyn`] into a single sorted
+/// sequence of references.
+///
+/// Both slices must be sorted using the same type-grouped order that
+/// `sort_dyn` uses, otherwise the result is unspecified (but safe).
+/// The merge is stable: of equal elements, those from `left` come first.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn, dyn_merge};
+/// let mut left: Vec<Box<dyn DynOrd>> = vec![Box::new(1), Box::new("qux"), Box::new(4)];
+/// let mut right: Vec<Box<dyn DynOrd>> = vec![Box::new("baz"), Box::new(2), Box::new(3)];
+///
+/// sort_dyn(&mut left);
+/// sort_dyn(&mut right);
+///
+/// let merged = dyn_merge(&left, &right);
+///
+/// let ints: Vec<i32> = merged.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+/// let strs: Vec<&str> = merged.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+///
+/// assert_eq!(merged.len(), 6);
+/// assert_eq!(ints, [1, 2, 3, 4]);
+/// assert_eq!(strs, ["baz", "qux"]);
+/// ```
+pub fn dyn_merge<'a>(left: &'a [Box<dyn DynOrd>], right: &'a [Box<dyn DynOrd>]) -> Vec<&'a dyn DynOrd> {
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut i = 0;
+    let mut j = 0;
+
+    while i < left.len() && j < right.len() {
+        if total_cmp(&*left[i], &*right[j]) == Ordering::Greater {
+            merged.push(&*right[j]);
+            j += 1;
+        } else {
+            merged.push(&*left[i]);
+            i += 1;
+        }
+    }
+
+    merged.extend(left[i..].iter().map(|x| &**x));
+    merged.extend(right[j..].iter().map(|x| &**x));
+    merged
+}
+
+/// Removes consecutive equal elements from a vector of heterogeneous
+/// values.
+///
+/// Like `Vec::dedup`, this only removes repeated elements that are
+/// adjacent. Values of different types are never equal.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_dedup;
+/// let mut values: Vec<Box<dyn DynEq>> = vec![
+///     Box::new(1),
+///     Box::new(1),
+///     Box::new(1_u8),
+///     Box::new("qux"),
+///     Box::new(1),
+/// ];
+///
+/// dyn_dedup(&mut values);
+///
+/// assert_eq!(values.len(), 4);
+/// ```
+pub fn dyn_dedup(v: &mut Vec<Box<dyn DynEq>>) {
+    v.dedup_by(|a, b| **a == **b);
+}
+
+/// Counts the distinct values in a slice of heterogeneous values.
+///
+/// Values are distinct if no earlier element is equal to them according
+/// to `DynEq`. Since `DynEq` values can't be sorted or hashed, this takes
+/// quadratic time, O(n²) comparisons for a slice of length n; for long
+/// slices, use [`dyn_count_distinct_sorted`] on sorted values or
+/// [`dyn_count_distinct_hashed`] on hashable values instead. Note that
+/// values which are not equal to themselves (e.g. floating-point NaNs)
+/// are always counted as distinct.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_count_distinct;
+/// let values: Vec<Box<dyn DynEq>> = vec![
+///     Box::new(1),
+///     Box::new("qux"),
+///     Box::new(1),
+///     Box::new(1_u8),
+///     Box::new("qux"),
+/// ];
+///
+/// assert_eq!(dyn_count_distinct(&values), 3);
+/// assert_eq!(dyn_count_distinct(&[]), 0);
+/// ```
+pub fn dyn_count_distinct(slice: &[Box<dyn DynEq>]) -> usize {
+    slice
+        .iter()
+        .enumerate()
+        .filter(|&(i, x)| !slice[..i].iter().any(|y| **y == **x))
+        .count()
+}
+
+/// Counts the distinct values in a slice of heterogeneous values that is
+/// sorted as if by [`sort_dyn`].
+///
+/// This is like [`dyn_count_distinct`], but it takes linear time, since
+/// equal values of a sorted slice are adjacent. The result is unspecified
+/// if the slice is not sorted.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn, dyn_count_distinct_sorted};
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(1),
+///     Box::new("qux"),
+///     Box::new(f64::NAN),
+///     Box::new(1),
+///     Box::new(f64::NAN),
+///     Box::new("qux"),
+/// ];
+///
+/// sort_dyn(&mut values);
+///
+/// assert_eq!(dyn_count_distinct_sorted(&values), 4);
+/// assert_eq!(dyn_count_distinct_sorted(&[]), 0);
+/// ```
+pub fn dyn_count_distinct_sorted(slice: &[Box<dyn DynOrd>]) -> usize {
+    let runs = slice.windows(2).filter(|w| *w[0] != *w[1]).count();
+
+    runs + usize::from(!slice.is_empty())
+}
+
+/// Counts the distinct values in a slice of hashable heterogeneous values.
+///
+/// This is like [`dyn_count_distinct`], but it takes linear time.
+///
+/// ```
+/// # use dyn_ord::DynHash;
+/// # use dyn_ord::util::dyn_count_distinct_hashed;
+/// let values: Vec<Box<dyn DynHash>> = vec![
+///     Box::new(1),
+///     Box::new("qux"),
+///     Box::new(1),
+///     Box::new(1_u8),
+///     Box::new("qux"),
+/// ];
+///
+/// assert_eq!(dyn_count_distinct_hashed(&values), 3);
+/// assert_eq!(dyn_count_distinct_hashed(&[]), 0);
+/// ```
+#[cfg(feature = "std")]
+pub fn dyn_count_distinct_hashed(slice: &[Box<dyn DynHash>]) -> usize {
+    let mut seen = HashSet::with_capacity(slice.len());
+
+    slice.iter().filter(|x| seen.insert(&***x)).count()
+}
+
+/// Returns one element of each distinct value in a slice of heterogeneous
+/// values, in the order of their first occurrence.
+///
+/// Unlike [`dyn_dedup`], this doesn't require equal elements to be
+/// adjacent. Like [`dyn_count_distinct`], this takes quadratic time;
+/// for long slices of hashable values, use [`dyn_stable_unique_hashed`]
+/// instead. Values which are not equal to themselves (e.g. floating-point
+/// NaNs) are always kept.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_stable_unique;
+/// let values: Vec<Box<dyn DynEq>> = vec![
+///     Box::new(1),
+///     Box::new("qux"),
+///     Box::new(1),
+///     Box::new(1_u8),
+///     Box::new("qux"),
+/// ];
+///
+/// let unique = dyn_stable_unique(&values);
+///
+/// assert_eq!(unique.len(), 3);
+/// assert!(*unique[0] == 1);
+/// assert!(*unique[1] == "qux");
+/// assert!(*unique[2] == 1_u8);
+/// ```
+pub fn dyn_stable_unique(slice: &[Box<dyn DynEq>]) -> Vec<&dyn DynEq> {
+    let mut unique: Vec<&dyn DynEq> = Vec::new();
+
+    for x in slice {
+        if !unique.iter().any(|y| **y == **x) {
+            unique.push(&**x);
+        }
+    }
+
+    unique
+}
+
+/// Returns one element of each distinct value in a slice of hashable
+/// heterogeneous values, in the order of their first occurrence.
+///
+/// This is like [`dyn_stable_unique`], but it takes linear time.
+///
+/// ```
+/// # use dyn_ord::DynHash;
+/// # use dyn_ord::util::dyn_stable_unique_hashed;
+/// let values: Vec<Box<dyn DynHash>> = vec![
+///     Box::new(1),
+///     Box::new("qux"),
+///     Box::new(1),
+///     Box::new(1_u8),
+///     Box::new("qux"),
+/// ];
+///
+/// let unique = dyn_stable_unique_hashed(&values);
+///
+/// assert_eq!(unique.len(), 3);
+/// assert!(*unique[0] == *(&1 as &dyn DynHash));
+/// assert!(*unique[1] == *(&"qux" as &dyn DynHash));
+/// assert!(*unique[2] == *(&1_u8 as &dyn DynHash));
+/// ```
+#[cfg(feature = "std")]
+pub fn dyn_stable_unique_hashed(slice: &[Box<dyn DynHash>]) -> Vec<&dyn DynHash> {
+    let mut seen = HashSet::with_capacity(slice.len());
+
+    slice
+        .iter()
+        .map(|x| &**x)
+        .filter(|x| seen.insert(*x))
+        .collect()
+}
+
+/// Removes consecutive elements of a vector of heterogeneous values
+/// that map to the same key.
+///
+/// ```
+/// # use dyn_ord::{DynEq, type_id_of};
+/// # use dyn_ord::util::dyn_dedup_by_key;
+/// let mut values: Vec<Box<dyn DynEq>> = vec![
+///     Box::new(1),
+///     Box::new(2),
+///     Box::new("qux"),
+///     Box::new("baz"),
+///     Box::new(3),
+/// ];
+///
+/// // keep only the first value of each run of a given type
+/// dyn_dedup_by_key(&mut values, type_id_of);
+///
+/// assert_eq!(values.len(), 3);
+/// assert_eq!(values[0].downcast_ref::<i32>(), Some(&1));
+/// assert_eq!(values[1].downcast_ref::<&str>(), Some(&"qux"));
+/// assert_eq!(values[2].downcast_ref::<i32>(), Some(&3));
+/// ```
+pub fn dyn_dedup_by_key<K, F>(v: &mut Vec<Box<dyn DynEq>>, mut key: F)
+where
+    K: PartialEq,
+    F: FnMut(&dyn DynEq) -> K,
+{
+    v.dedup_by_key(|x| key(&**x));
+}
+
+/// Splits a slice of heterogeneous values into maximal runs of elements
+/// of the same type.
+///
+/// This is most useful for slices sorted by type (e.g. by [`sort_dyn`]),
+/// in which case there is exactly one run per type. See
+/// [`dyn_group_by_type`] for grouping unsorted slices.
+///
+/// ```
+/// # use core::any::TypeId;
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_partition_by_type;
+/// let values: Vec<Box<dyn DynEq>> = vec![
+///     Box::new(1),
+///     Box::new(2),
+///     Box::new("qux"),
+///     Box::new(3),
+/// ];
+///
+/// let runs = dyn_partition_by_type(&values);
+///
+/// assert_eq!(runs.len(), 3);
+/// assert_eq!(runs[0].0, TypeId::of::<i32>());
+/// assert_eq!(runs[0].1.len(), 2);
+/// assert_eq!(runs[1].0, TypeId::of::<&str>());
+/// assert_eq!(runs[2].1.len(), 1);
+/// ```
+pub fn dyn_partition_by_type(slice: &[Box<dyn DynEq>]) -> Vec<(TypeId, &[Box<dyn DynEq>])> {
+    slice
+        .chunk_by(|a, b| same_type(&**a, &**b))
+        .map(|run| (type_id_of(&*run[0]), run))
+        .collect()
+}
+
+/// Lazily splits an iterator of heterogeneous values into runs of
+/// consecutive elements of the same type.
+///
+/// This is the streaming counterpart of [`dyn_partition_by_type`]: each
+/// run is yielded as soon as the first element of a different type (or
+/// the end of the input) is reached.
+///
+/// ```
+/// # use core::any::TypeId;
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_chunk_by_type;
+/// let values: Vec<Box<dyn DynEq>> = vec![
+///     Box::new(1),
+///     Box::new(2),
+///     Box::new("qux"),
+///     Box::new(3),
+/// ];
+///
+/// let mut runs = dyn_chunk_by_type(values.into_iter());
+///
+/// let (type_id, run) = runs.next().unwrap();
+/// assert_eq!(type_id, TypeId::of::<i32>());
+/// assert_eq!(run.len(), 2);
+///
+/// let (type_id, run) = runs.next().unwrap();
+/// assert_eq!(type_id, TypeId::of::<&str>());
+/// assert!(*run[0] == "qux");
+///
+/// assert_eq!(runs.next().map(|(_, run)| run.len()), Some(1));
+/// assert!(runs.next().is_none());
+/// ```
+pub fn dyn_chunk_by_type<I>(iter: I) -> impl Iterator<Item = (TypeId, Vec<Box<dyn DynEq>>)>
+where
+    I: Iterator<Item = Box<dyn DynEq>>,
+{
+    let mut iter = iter.peekable();
+
+    core::iter::from_fn(move || {
+        let first = iter.next()?;
+        let type_id = type_id_of(&*first);
+        let mut run = Vec::new();
+        run.push(first);
+
+        while let Some(next) = iter.next_if(|x| type_id_of(&**x) == type_id) {
+            run.push(next);
+        }
+
+        Some((type_id, run))
+    })
+}
+
+/// Groups the elements of a slice of heterogeneous values by type.
+///
+/// Unlike [`dyn_partition_by_type`], this does not require elements of
+/// the same type to be adjacent. The relative order of elements within
+/// each group is preserved.
+///
+/// ```
+/// # use core::any::TypeId;
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_group_by_type;
+/// let values: Vec<Box<dyn DynEq>> = vec![
+///     Box::new(1),
+///     Box::new("qux"),
+///     Box::new(2),
+///     Box::new("baz"),
+///     Box::new('x'),
+/// ];
+///
+/// let groups = dyn_group_by_type(&values);
+///
+/// assert_eq!(groups.len(), 3);
+/// assert_eq!(groups[&TypeId::of::<i32>()].len(), 2);
+/// assert_eq!(groups[&TypeId::of::<&str>()][1].downcast_ref::<&str>(), Some(&"baz"));
+/// assert_eq!(groups[&TypeId::of::<char>()].len(), 1);
+/// ```
+#[cfg(feature = "std")]
+pub fn dyn_group_by_type(slice: &[Box<dyn DynEq>]) -> HashMap<TypeId, Vec<&dyn DynEq>> {
+    let mut groups: HashMap<TypeId, Vec<&dyn DynEq>> = HashMap::new();
+
+    for x in slice {
+        groups.entry(type_id_of(&**x)).or_default().push(&**x);
+    }
+
+    groups
+}
+
+/// Counts the elements of each concrete type in a slice of heterogeneous
+/// values.
+///
+/// ```
+/// # use core::any::TypeId;
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_count_by_type;
+/// let values: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new("qux"), Box::new(2), Box::new(3)];
+///
+/// let counts = dyn_count_by_type(&values);
+///
+/// assert_eq!(counts.len(), 2);
+/// assert_eq!(counts[&TypeId::of::<i32>()], 3);
+/// assert_eq!(counts[&TypeId::of::<&str>()], 1);
+/// ```
+#[cfg(feature = "std")]
+pub fn dyn_count_by_type(slice: &[Box<dyn DynEq>]) -> HashMap<TypeId, usize> {
+    let mut counts = HashMap::new();
+
+    for x in slice {
+        *counts.entry(type_id_of(&**x)).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// Counts the elements of each concrete type in a slice of heterogeneous
+/// values, keyed by the name of the type.
+///
+/// This is like [`dyn_count_by_type`], but more useful for debugging.
+/// As with `core::any::type_name`, the names are not guaranteed to be
+/// unique, so the counts of distinct types may be merged.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_type_name_histogram;
+/// let values: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new("qux"), Box::new(2), Box::new(3)];
+///
+/// let histogram = dyn_type_name_histogram(&values);
+///
+/// assert_eq!(histogram["i32"], 3);
+/// assert_eq!(histogram["&str"], 1);
+/// ```
+#[cfg(feature = "std")]
+pub fn dyn_type_name_histogram(slice: &[Box<dyn DynEq>]) -> HashMap<&'static str, usize> {
+    let mut counts = HashMap::new();
+
+    for x in slice {
+        *counts.entry(type_name_of(&**x)).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// The detailed result of comparing two heterogeneous values, as returned
+/// by [`dyn_ord_cmp`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DynCmpResult {
+    /// The values are of the same type, and they are ordered.
+    Ordered(Ordering),
+    /// The values are of the same type, but they are incomparable
+    /// (e.g. because one of them is a floating-point NaN).
+    IncomparableSameType,
+    /// The values are of different types.
+    DifferentTypes,
+}
+
+/// Compares two heterogeneous values, distinguishing between values of
+/// different types and incomparable values of the same type.
+///
+/// Both of these cases are represented by `None` when comparing using
+/// `PartialOrd`.
+///
+/// ```
+/// # use core::cmp::Ordering;
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{dyn_ord_cmp, DynCmpResult};
+/// assert_eq!(dyn_ord_cmp(&1, &2), DynCmpResult::Ordered(Ordering::Less));
+/// assert_eq!(dyn_ord_cmp(&f64::NAN, &1.0), DynCmpResult::IncomparableSameType);
+/// assert_eq!(dyn_ord_cmp(&1, &"qux"), DynCmpResult::DifferentTypes);
+/// ```
+pub fn dyn_ord_cmp(a: &dyn DynOrd, b: &dyn DynOrd) -> DynCmpResult {
+    if !same_type_ord(a, b) {
+        return DynCmpResult::DifferentTypes;
+    }
+
+    match a.partial_cmp(b) {
+        Some(ordering) => DynCmpResult::Ordered(ordering),
+        None => DynCmpResult::IncomparableSameType,
+    }
+}
+
+/// Returns the lesser of two values, or `None` if they are incomparable
+/// (e.g. because they are of different types).
+///
+/// If the values are equal, `a` is returned.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::dyn_min;
+/// let x: &dyn DynOrd = &1;
+/// let y: &dyn DynOrd = &2;
+/// let z: &dyn DynOrd = &"qux";
+///
+/// assert_eq!(dyn_min(x, y).and_then(|m| m.downcast_ref::<i32>()), Some(&1));
+/// assert!(dyn_min(x, z).is_none());
+/// ```
+pub fn dyn_min<'a>(a: &'a dyn DynOrd, b: &'a dyn DynOrd) -> Option<&'a dyn DynOrd> {
+    match a.partial_cmp(b)? {
+        Ordering::Less | Ordering::Equal => Some(a),
+        Ordering::Greater => Some(b),
+    }
+}
+
+/// Returns the greater of two values, or `None` if they are incomparable
+/// (e.g. because they are of different types).
+///
+/// If the values are equal, `a` is returned.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::dyn_max;
+/// let x: &dyn DynOrd = &1.5;
+/// let y: &dyn DynOrd = &2.5;
+/// let z: &dyn DynOrd = &f64::NAN;
+///
+/// assert_eq!(dyn_max(x, y).and_then(|m| m.downcast_ref::<f64>()), Some(&2.5));
+/// assert!(dyn_max(x, z).is_none());
+/// ```
+pub fn dyn_max<'a>(a: &'a dyn DynOrd, b: &'a dyn DynOrd) -> Option<&'a dyn DynOrd> {
+    match a.partial_cmp(b)? {
+        Ordering::Greater | Ordering::Equal => Some(a),
+        Ordering::Less => Some(b),
+    }
+}
+
+/// Restricts a value to the interval `[min, max]`.
+///
+/// Returns `min` if `val` is less than `min`, `max` if `val` is greater
+/// than `max`, and `val` otherwise. Returns `None` if any two of the
+/// values are incomparable (e.g. because they are of different types).
+///
+/// # Panics
+///
+/// Panics if `min > max`.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::dyn_clamp;
+/// let min: &dyn DynOrd = &0;
+/// let max: &dyn DynOrd = &10;
+///
+/// assert_eq!(dyn_clamp(&-5, min, max).and_then(|x| x.downcast_ref::<i32>()), Some(&0));
+/// assert_eq!(dyn_clamp(&5, min, max).and_then(|x| x.downcast_ref::<i32>()), Some(&5));
+/// assert_eq!(dyn_clamp(&15, min, max).and_then(|x| x.downcast_ref::<i32>()), Some(&10));
+/// assert!(dyn_clamp(&5_u8, min, max).is_none());
+/// ```
+pub fn dyn_clamp<'a>(
+    val: &'a dyn DynOrd,
+    min: &'a dyn DynOrd,
+    max: &'a dyn DynOrd,
+) -> Option<&'a dyn DynOrd> {
+    assert!(
+        min.partial_cmp(max)? != Ordering::Greater,
+        "dyn_clamp: min must not be greater than max",
+    );
+
+    if val.partial_cmp(min)? == Ordering::Less {
+        Some(min)
+    } else if val.partial_cmp(max)? == Ordering::Greater {
+        Some(max)
+    } else {
+        Some(val)
+    }
+}
+
+/// Returns the minimum of each window of `window` consecutive elements.
+///
+/// The result has one entry per window, in order, so it is empty if the
+/// slice is shorter than `window`. An entry is `None` if any two elements
+/// of its window are incomparable (e.g. because they are of different
+/// types). Of equal minimal elements, the first one is returned. This
+/// takes `O(n * window)` time.
+///
+/// # Panics
+///
+/// Panics if `window` is 0.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::dyn_window_min;
+/// let values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(3),
+///     Box::new(1),
+///     Box::new(2),
+///     Box::new("qux"),
+/// ];
+///
+/// let mins: Vec<Option<i32>> = dyn_window_min(&values, 2)
+///     .into_iter()
+///     .map(|min| min.and_then(|x| x.downcast_ref().copied()))
+///     .collect();
+///
+/// assert_eq!(mins, [Some(1), Some(1), None]);
+/// ```
+pub fn dyn_window_min(slice: &[Box<dyn DynOrd>], window: usize) -> Vec<Option<&dyn DynOrd>> {
+    slice
+        .windows(window)
+        .map(|w| w[1..].iter().try_fold(&*w[0], |min, x| dyn_min(min, &**x)))
+        .collect()
+}
+
+/// Returns the maximum of each window of `window` consecutive elements.
+///
+/// This is the counterpart of [`dyn_window_min`]. Of equal maximal
+/// elements, the first one is returned.
+///
+/// # Panics
+///
+/// Panics if `window` is 0.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::dyn_window_max;
+/// let values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(1.5),
+///     Box::new(f64::NAN),
+///     Box::new(0.5),
+///     Box::new(2.5),
+/// ];
+///
+/// let maxes: Vec<Option<f64>> = dyn_window_max(&values, 2)
+///     .into_iter()
+///     .map(|max| max.and_then(|x| x.downcast_ref().copied()))
+///     .collect();
+///
+/// assert_eq!(maxes, [None, None, Some(2.5)]);
+/// assert!(dyn_window_max(&values, 5).is_empty());
+/// ```
+pub fn dyn_window_max(slice: &[Box<dyn DynOrd>], window: usize) -> Vec<Option<&dyn DynOrd>> {
+    slice
+        .windows(window)
+        .map(|w| w[1..].iter().try_fold(&*w[0], |max, x| dyn_max(max, &**x)))
+        .collect()
+}
+
+/// Returns the element of the slice with the minimum key, or `None` if
+/// the slice is empty.
+///
+/// Since the keys are of a single type `K`, which is `Ord`, elements of
+/// different types never need to be compared directly. Like
+/// `Iterator::min_by_key`, this returns the first of several minimal
+/// elements.
+///
+/// ```
+/// # use dyn_ord::{DynOrd, type_name_of};
+/// # use dyn_ord::util::dyn_min_by_key;
+/// let values: Vec<Box<dyn DynOrd>> = vec![Box::new(1_u64), Box::new('x'), Box::new(2_u8)];
+/// let shortest_name = dyn_min_by_key(&values, |x| type_name_of(x).len());
+///
+/// assert_eq!(shortest_name.and_then(|x| x.downcast_ref::<u8>()), Some(&2));
+/// ```
+pub fn dyn_min_by_key<K, F>(slice: &[Box<dyn DynOrd>], mut key: F) -> Option<&dyn DynOrd>
+where
+    K: Ord,
+    F: FnMut(&dyn DynOrd) -> K,
+{
+    slice.iter().map(|x| &**x).min_by_key(|x| key(*x))
+}
+
+/// Returns the element of the slice with the maximum key, or `None` if
+/// the slice is empty.
+///
+/// Like `Iterator::max_by_key`, this returns the last of several maximal
+/// elements.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::dyn_max_by_key;
+/// let values: Vec<Box<dyn DynOrd>> = vec![Box::new("qux"), Box::new(42), Box::new("bar")];
+/// let is_str = dyn_max_by_key(&values, |x| x.is::<&str>());
+///
+/// assert_eq!(is_str.and_then(|x| x.downcast_ref::<&str>()), Some(&"bar"));
+/// assert!(dyn_max_by_key(&[], |x| x.is::<&str>()).is_none());
+/// ```
+pub fn dyn_max_by_key<K, F>(slice: &[Box<dyn DynOrd>], mut key: F) -> Option<&dyn DynOrd>
+where
+    K: Ord,
+    F: FnMut(&dyn DynOrd) -> K,
+{
+    slice.iter().map(|x| &**x).max_by_key(|x| key(*x))
+}
+
+/// Returns `true` if all elements of the slice are equal.
+///
+/// Only adjacent elements are compared, so this relies on equality
+/// being transitive. Returns `true` for empty and single-element slices.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_eq_all;
+/// assert!(dyn_eq_all(&[]));
+/// assert!(dyn_eq_all(&[&1]));
+/// assert!(dyn_eq_all(&[&1, &1, &1]));
+/// assert!(!dyn_eq_all(&[&1, &1, &2]));
+/// assert!(!dyn_eq_all(&[&1, &1_u64]));
+/// ```
+pub fn dyn_eq_all(slice: &[&dyn DynEq]) -> bool {
+    slice.windows(2).all(|w| *w[0] == *w[1])
+}
+
+/// Returns `true` if any element of the slice is equal to `target`.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_eq_any;
+/// let haystack: [&dyn DynEq; 3] = [&1, &"qux", &'x'];
+///
+/// assert!(dyn_eq_any(&"qux", &haystack));
+/// assert!(!dyn_eq_any(&"baz", &haystack));
+/// assert!(!dyn_eq_any(&1_u8, &haystack));
+/// assert!(!dyn_eq_any(&1, &[]));
+/// ```
+pub fn dyn_eq_any(target: &dyn DynEq, slice: &[&dyn DynEq]) -> bool {
+    slice.iter().any(|x| *target == **x)
+}
+
+/// Returns `true` if every element of the slice is equal to `target`.
+///
+/// Unlike [`dyn_eq_all`], this compares each element with `target`,
+/// so it doesn't rely on equality being transitive. Returns `true` for
+/// an empty slice.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_all_equal_to;
+/// assert!(dyn_all_equal_to(&1, &[&1, &1, &1]));
+/// assert!(!dyn_all_equal_to(&1, &[&1, &2]));
+/// assert!(!dyn_all_equal_to(&1, &[&1, &1_u64]));
+/// assert!(!dyn_all_equal_to(&f64::NAN, &[&f64::NAN]));
+/// assert!(dyn_all_equal_to(&1, &[]));
+/// ```
+pub fn dyn_all_equal_to(target: &dyn DynEq, slice: &[&dyn DynEq]) -> bool {
+    slice.iter().all(|x| *target == **x)
+}
+
+/// Compares values of two specific, possibly different types using a
+/// user-provided comparison function.
+///
+/// If `a` is of type `A` and `b` is of type `B`, returns the result of
+/// `eq(a, b)`. If it's the other way around, the arguments are swapped,
+/// so that the comparison is symmetric. Returns `None` if the values
+/// are not of these types. For comparing many pairs of types with
+/// custom rules, see [`DynEqRegistry`](crate::DynEqRegistry).
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_eq_cross_type;
+/// let eq = |a: &u32, b: &i64| i64::from(*a) == *b;
+///
+/// assert_eq!(dyn_eq_cross_type(&42_u32, &42_i64, eq), Some(true));
+/// assert_eq!(dyn_eq_cross_type(&-1_i64, &u32::MAX, eq), Some(false));
+/// assert_eq!(dyn_eq_cross_type(&42_u32, &42_u32, eq), None);
+/// ```
+pub fn dyn_eq_cross_type<A, B, F>(a: &dyn DynEq, b: &dyn DynEq, eq: F) -> Option<bool>
+where
+    A: Any,
+    B: Any,
+    F: FnOnce(&A, &B) -> bool,
+{
+    if let (Some(a), Some(b)) = (a.downcast_ref::<A>(), b.downcast_ref::<B>()) {
+        Some(eq(a, b))
+    } else if let (Some(a), Some(b)) = (b.downcast_ref::<A>(), a.downcast_ref::<B>()) {
+        Some(eq(a, b))
+    } else {
+        None
+    }
+}
+
+/// Folds the elements of a slice of heterogeneous values into an
+/// accumulator, in order.
+///
+/// This is `Iterator::fold` over the unboxed elements.
+///
+/// ```
+/// # use dyn_ord::{DynEq, type_name_of};
+/// # use dyn_ord::util::dyn_fold;
+/// let values: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new("qux"), Box::new(2)];
+///
+/// let ints = dyn_fold(&values, 0, |sum, x| sum + x.downcast_ref::<i32>().unwrap_or(&0));
+/// let names = dyn_fold(&values, String::new(), |names, x| names + type_name_of(x) + " ");
+///
+/// assert_eq!(ints, 3);
+/// assert_eq!(names, "i32 &str i32 ");
+/// ```
+pub fn dyn_fold<'a, Acc, F>(slice: &'a [Box<dyn DynEq>], init: Acc, mut f: F) -> Acc
+where
+    F: FnMut(Acc, &'a dyn DynEq) -> Acc,
+{
+    slice.iter().fold(init, |acc, x| f(acc, &**x))
+}
+
+/// Folds the elements of a slice of heterogeneous values into an
+/// accumulator, in order, stopping at the first error.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_try_fold;
+/// // returns the first value that is not an `i32` as the error
+/// fn sum(values: &[Box<dyn DynEq>]) -> Result<i32, &dyn DynEq> {
+///     dyn_try_fold(values, 0, |sum, x| x.downcast_ref::<i32>().map(|x| sum + x).ok_or(x))
+/// }
+///
+/// let ints: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new(2)];
+/// let mixed: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new("qux"), Box::new(2)];
+///
+/// assert_eq!(sum(&ints).ok(), Some(3));
+/// assert!(sum(&mixed).is_err_and(|x| *x == "qux"));
+/// ```
+pub fn dyn_try_fold<'a, Acc, E, F>(slice: &'a [Box<dyn DynEq>], init: Acc, mut f: F) -> Result<Acc, E>
+where
+    F: FnMut(Acc, &'a dyn DynEq) -> Result<Acc, E>,
+{
+    slice.iter().try_fold(init, |acc, x| f(acc, &**x))
+}
+
+/// Returns the first element of the slice that is equal to `target`.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_find;
+/// let values: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new("qux"), Box::new(2)];
+///
+/// assert!(dyn_find(&values, &"qux").is_some_and(|x| x.is::<&str>()));
+/// assert!(dyn_find(&values, &"baz").is_none());
+/// ```
+pub fn dyn_find<'a>(slice: &'a [Box<dyn DynEq>], target: &dyn DynEq) -> Option<&'a dyn DynEq> {
+    slice.iter().map(|x| &**x).find(|x| **x == *target)
+}
+
+/// Returns the last element of the slice that is equal to `target`.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_find_last;
+/// let values: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new("qux"), Box::new(2)];
+///
+/// assert!(dyn_find_last(&values, &"qux").is_some_and(|x| x.is::<&str>()));
+/// assert!(dyn_find_last(&values, &"baz").is_none());
+/// ```
+pub fn dyn_find_last<'a>(slice: &'a [Box<dyn DynEq>], target: &dyn DynEq) -> Option<&'a dyn DynEq> {
+    slice.iter().map(|x| &**x).rfind(|x| **x == *target)
+}
+
+/// Returns the index of the first element of the slice that is equal
+/// to `target`.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_position;
+/// let values: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new("qux"), Box::new(1)];
+///
+/// assert_eq!(dyn_position(&values, &1), Some(0));
+/// assert_eq!(dyn_position(&values, &"qux"), Some(1));
+/// assert_eq!(dyn_position(&values, &1_u8), None);
+/// ```
+pub fn dyn_position(slice: &[Box<dyn DynEq>], target: &dyn DynEq) -> Option<usize> {
+    slice.iter().position(|x| **x == *target)
+}
+
+/// Returns the index of the last element of the slice that is equal
+/// to `target`.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_rposition;
+/// let values: Vec<Box<dyn DynEq>> = vec![Box::new(1), Box::new("qux"), Box::new(1)];
+///
+/// assert_eq!(dyn_rposition(&values, &1), Some(2));
+/// assert_eq!(dyn_rposition(&values, &"qux"), Some(1));
+/// assert_eq!(dyn_rposition(&values, &1_u8), None);
+/// ```
+pub fn dyn_rposition(slice: &[Box<dyn DynEq>], target: &dyn DynEq) -> Option<usize> {
+    slice.iter().rposition(|x| **x == *target)
+}
+
+/// Returns the number of elements of a slice sorted by [`sort_dyn`] that
+/// are strictly less than `query`, in the type-grouped order.
+///
+/// This is also the index of the first element not less than `query`.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn, dyn_rank};
+/// let mut values: Vec<Box<dyn DynOrd>> = (1..=10).map(|i| Box::new(i) as Box<dyn DynOrd>).collect();
+///
+/// sort_dyn(&mut values);
+///
+/// assert_eq!(dyn_rank(&values, &1), 0);
+/// assert_eq!(dyn_rank(&values, &5), 4);
+/// assert_eq!(dyn_rank(&values, &100), 10);
+/// ```
+pub fn dyn_rank(slice: &[Box<dyn DynOrd>], query: &dyn DynOrd) -> usize {
+    slice.partition_point(|x| total_cmp(&**x, query) == Ordering::Less)
+}
+
+/// Reorders the slice such that the element at index `k` is at its final
+/// sorted position in the type-grouped order of [`sort_dyn`], in which
+/// NaNs come before all other values of their type.
+///
+/// See `slice::select_nth_unstable` for the exact guarantees; elements
+/// before `k` are not greater, and elements after `k` are not less than
+/// the element at `k`.
+///
+/// # Panics
+///
+/// Panics if `k >= slice.len()`.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::dyn_select_nth;
+/// let mut values: Vec<Box<dyn DynOrd>> = [5, 1, 4, 2, 3]
+///     .iter()
+///     .map(|&i| Box::new(i) as Box<dyn DynOrd>)
+///     .collect();
+///
+/// dyn_select_nth(&mut values, 2);
+///
+/// assert_eq!(values[2].downcast_ref::<i32>(), Some(&3));
+/// ```
+pub fn dyn_select_nth(slice: &mut [Box<dyn DynOrd>], k: usize) {
+    slice.select_nth_unstable_by(k, |a, b| total_cmp(&**a, &**b));
+}
+
+/// Returns the elements common to two slices sorted by [`sort_dyn`].
+///
+/// Elements are considered equal if `DynOrd` compares them as equal;
+/// of each pair of equal elements, the one from `a` is returned. The
+/// result is sorted in the same order as the inputs.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn, dyn_intersection};
+/// let mut a: Vec<Box<dyn DynOrd>> = vec![Box::new(1), Box::new("qux"), Box::new(3), Box::new("baz")];
+/// let mut b: Vec<Box<dyn DynOrd>> = vec![Box::new("qux"), Box::new(3), Box::new(4), Box::new('x')];
+///
+/// sort_dyn(&mut a);
+/// sort_dyn(&mut b);
+///
+/// let common = dyn_intersection(&a, &b);
+///
+/// assert_eq!(common.len(), 2);
+/// assert!(common.iter().any(|x| x.downcast_ref() == Some(&3)));
+/// assert!(common.iter().any(|x| x.downcast_ref() == Some(&"qux")));
+/// ```
+pub fn dyn_intersection<'a>(a: &'a [Box<dyn DynOrd>], b: &'a [Box<dyn DynOrd>]) -> Vec<&'a dyn DynOrd> {
+    let mut common = Vec::new();
+    let mut i = 0;
+    let mut j = 0;
+
+    while i < a.len() && j < b.len() {
+        match total_cmp(&*a[i], &*b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                if *a[i] == *b[j] {
+                    common.push(&*a[i]);
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    common
+}
+
+/// Returns the elements present in either of two slices sorted by
+/// [`sort_dyn`].
+///
+/// Elements are considered equal if `DynOrd` compares them as equal;
+/// of each pair of equal elements, only the one from `a` is returned.
+/// The result is sorted in the same order as the inputs.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn, dyn_union};
+/// let mut a: Vec<Box<dyn DynOrd>> = vec![Box::new(1), Box::new("qux"), Box::new(3)];
+/// let mut b: Vec<Box<dyn DynOrd>> = vec![Box::new("qux"), Box::new(3), Box::new(4)];
+///
+/// sort_dyn(&mut a);
+/// sort_dyn(&mut b);
+///
+/// let all = dyn_union(&a, &b);
+/// let ints: Vec<i32> = all.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+///
+/// assert_eq!(all.len(), 4);
+/// assert_eq!(ints, [1, 3, 4]);
+/// ```
+pub fn dyn_union<'a>(a: &'a [Box<dyn DynOrd>], b: &'a [Box<dyn DynOrd>]) -> Vec<&'a dyn DynOrd> {
+    let mut all = Vec::with_capacity(a.len().max(b.len()));
+    let mut i = 0;
+    let mut j = 0;
+
+    while i < a.len() && j < b.len() {
+        match total_cmp(&*a[i], &*b[j]) {
+            Ordering::Less => {
+                all.push(&*a[i]);
+                i += 1;
+            }
+            Ordering::Greater => {
+                all.push(&*b[j]);
+                j += 1;
+            }
+            Ordering::Equal => {
+                all.push(&*a[i]);
+                if *a[i] != *b[j] {
+                    all.push(&*b[j]);
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    all.extend(a[i..].iter().map(|x| &**x));
+    all.extend(b[j..].iter().map(|x| &**x));
+    all
+}
+
+/// Returns the elements present in exactly one of two slices sorted by
+/// [`sort_dyn`].
+///
+/// Elements are considered equal if `DynOrd` compares them as equal, so
+/// incomparable elements, such as NaNs, are returned from both slices.
+/// The result is sorted in the same order as the inputs.
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::{sort_dyn, dyn_symmetric_diff};
+/// let mut a: Vec<Box<dyn DynOrd>> = vec![Box::new(1), Box::new("qux"), Box::new(3)];
+/// let mut b: Vec<Box<dyn DynOrd>> = vec![Box::new("qux"), Box::new(3), Box::new(4), Box::new('x')];
+///
+/// sort_dyn(&mut a);
+/// sort_dyn(&mut b);
+///
+/// let diff = dyn_symmetric_diff(&a, &b);
+/// let ints: Vec<i32> = diff.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+///
+/// assert_eq!(diff.len(), 3);
+/// assert_eq!(ints, [1, 4]);
+/// assert!(diff.iter().any(|x| x.downcast_ref() == Some(&'x')));
+/// ```
+pub fn dyn_symmetric_diff<'a>(a: &'a [Box<dyn DynOrd>], b: &'a [Box<dyn DynOrd>]) -> Vec<&'a dyn DynOrd> {
+    let mut diff = Vec::new();
+    let mut i = 0;
+    let mut j = 0;
+
+    while i < a.len() && j < b.len() {
+        match total_cmp(&*a[i], &*b[j]) {
+            Ordering::Less => {
+                diff.push(&*a[i]);
+                i += 1;
+            }
+            Ordering::Greater => {
+                diff.push(&*b[j]);
+                j += 1;
+            }
+            Ordering::Equal => {
+                if *a[i] != *b[j] {
+                    diff.push(&*a[i]);
+                    diff.push(&*b[j]);
+                }
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    diff.extend(a[i..].iter().map(|x| &**x));
+    diff.extend(b[j..].iter().map(|x| &**x));
+    diff
+}
+
+/// Sorts the nodes of a directed acyclic graph of heterogeneous values
+/// topologically.
+///
+/// Each edge `(a, b)` means that `a` must come before `b`. Nodes are
+/// identified using `DynEq`; of several equal nodes, the first occurrence
+/// is returned. Nodes without an ordering constraint between them are
+/// returned in the order of their first occurrence. Returns `None` if the
+/// graph contains a cycle. Values which are not equal to themselves (e.g.
+/// floating-point NaNs) are distinct nodes at every occurrence.
+///
+/// # Complexity
+///
+/// Since `DynEq` values can't be hashed, nodes are looked up by linear
+/// search, so this takes `O(n * e)` time for `n` distinct nodes and `e`
+/// edges. For hashable nodes, [`dyn_topological_sort_hashed`] takes
+/// linear time instead.
+///
+/// ```
+/// # use dyn_ord::DynEq;
+/// # use dyn_ord::util::dyn_topological_sort;
+/// let edges: Vec<(Box<dyn DynEq>, Box<dyn DynEq>)> = vec![
+///     (Box::new("parse"), Box::new(1)),
+///     (Box::new("lex"), Box::new("parse")),
+///     (Box::new(1), Box::new('x')),
+/// ];
+///
+/// let order = dyn_topological_sort(&edges).unwrap();
+///
+/// assert_eq!(order.len(), 4);
+/// assert!(*order[0] == "lex");
+/// assert!(*order[1] == "parse");
+/// assert!(*order[2] == 1);
+/// assert!(*order[3] == 'x');
+///
+/// let cyclic: Vec<(Box<dyn DynEq>, Box<dyn DynEq>)> = vec![
+///     (Box::new(1), Box::new("qux")),
+///     (Box::new("qux"), Box::new(1)),
+/// ];
+///
+/// assert!(dyn_topological_sort(&cyclic).is_none());
+/// ```
+#[allow(clippy::type_complexity)]
+pub fn dyn_topological_sort(edges: &[(Box<dyn DynEq>, Box<dyn DynEq>)]) -> Option<Vec<&dyn DynEq>> {
+    fn intern<'a>(nodes: &mut Vec<&'a dyn DynEq>, node: &'a dyn DynEq) -> usize {
+        nodes.iter().position(|x| **x == *node).unwrap_or_else(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        })
+    }
+
+    let mut nodes = Vec::new();
+    let mut indices = Vec::with_capacity(edges.len());
+
+    for (from, to) in edges {
+        let from = intern(&mut nodes, &**from);
+        let to = intern(&mut nodes, &**to);
+        indices.push((from, to));
+    }
+
+    kahn(nodes, &indices)
+}
+
+/// Sorts the nodes of a directed acyclic graph of hashable heterogeneous
+/// values topologically.
+///
+/// This is like [`dyn_topological_sort`], but nodes are looked up in a
+/// hash map, so it takes linear time in the number of edges.
+///
+/// ```
+/// # use dyn_ord::DynHash;
+/// # use dyn_ord::util::dyn_topological_sort_hashed;
+/// let edges: Vec<(Box<dyn DynHash>, Box<dyn DynHash>)> = vec![
+///     (Box::new("parse"), Box::new(1)),
+///     (Box::new("lex"), Box::new("parse")),
+///     (Box::new(1), Box::new('x')),
+/// ];
+///
+/// let order = dyn_topological_sort_hashed(&edges).unwrap();
+///
+/// assert_eq!(order.len(), 4);
+/// assert!(*order[0] == *(&"lex" as &dyn DynHash));
+/// assert!(*order[3] == *(&'x' as &dyn DynHash));
+///
+/// let cyclic: Vec<(Box<dyn DynHash>, Box<dyn DynHash>)> = vec![
+///     (Box::new(1), Box::new(1)),
+/// ];
+///
+/// assert!(dyn_topological_sort_hashed(&cyclic).is_none());
+/// ```
+#[cfg(feature = "std")]
+#[allow(clippy::type_complexity)]
+pub fn dyn_topological_sort_hashed(edges: &[(Box<dyn DynHash>, Box<dyn DynHash>)]) -> Option<Vec<&dyn DynHash>> {
+    fn intern<'a>(
+        nodes: &mut Vec<&'a dyn DynHash>,
+        index: &mut HashMap<&'a dyn DynHash, usize>,
+        node: &'a dyn DynHash,
+    ) -> usize {
+        *index.entry(node).or_insert_with(|| {
+            nodes.push(node);
+            nodes.len() - 1
+        })
+    }
+
+    let mut nodes = Vec::new();
+    let mut index = HashMap::new();
+    let mut indices = Vec::with_capacity(edges.len());
+
+    for (from, to) in edges {
+        let from = intern(&mut nodes, &mut index, &**from);
+        let to = intern(&mut nodes, &mut index, &**to);
+        indices.push((from, to));
+    }
+
+    kahn(nodes, &indices)
+}
+
+/// Sorts `nodes` topologically using Kahn's algorithm, given the edges
+/// between them as pairs of indices. Returns `None` if there is a cycle.
+fn kahn<N: Copy>(nodes: Vec<N>, edges: &[(usize, usize)]) -> Option<Vec<N>> {
+    let mut successors: Vec<Vec<usize>> = alloc::vec![Vec::new(); nodes.len()];
+    let mut in_degrees = alloc::vec![0_usize; nodes.len()];
+
+    for &(from, to) in edges {
+        successors[from].push(to);
+        in_degrees[to] += 1;
+    }
+
+    let mut queue: VecDeque<usize> = (0..nodes.len()).filter(|&i| in_degrees[i] == 0).collect();
+    let mut order = Vec::with_capacity(nodes.len());
+
+    while let Some(i) = queue.pop_front() {
+        order.push(nodes[i]);
+
+        for &j in &successors[i] {
+            in_degrees[j] -= 1;
+            if in_degrees[j] == 0 {
+                queue.push_back(j);
+            }
+        }
+    }
+
+    if order.len() == nodes.len() {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+/// Extension methods for iterators over boxed heterogeneous values.
+///
+/// Values are compared using the same type-grouped order as [`sort_dyn`].
+///
+/// ```
+/// # use dyn_ord::DynOrd;
+/// # use dyn_ord::util::DynOrdIterExt;
+/// fn values() -> impl Iterator<Item = Box<dyn DynOrd>> {
+///     vec![
+///         Box::new(3) as Box<dyn DynOrd>,
+///         Box::new(1),
+///         Box::new(2),
+///     ]
+///     .into_iter()
+/// }
+///
+/// assert_eq!(values().dyn_min().unwrap().downcast_ref::<i32>(), Some(&1));
+/// assert_eq!(values().dyn_max().unwrap().downcast_ref::<i32>(), Some(&3));
+///
+/// let sorted: Vec<i32> = values()
+///     .dyn_sorted()
+///     .iter()
+///     .filter_map(|x| x.downcast_ref().copied())
+///     .collect();
+///
+/// assert_eq!(sorted, [1, 2, 3]);
+/// ```
+pub trait DynOrdIterExt: Iterator<Item = Box<dyn DynOrd>> + Sized {
+    /// Returns the least element of the iterator, or `None` if it is empty.
+    ///
+    /// If several elements are equally minimal, the first one is returned.
+    fn dyn_min(self) -> Option<Self::Item> {
+        self.min_by(|a, b| total_cmp(&**a, &**b))
+    }
+
+    /// Returns the greatest element of the iterator, or `None` if it is
+    /// empty.
+    ///
+    /// If several elements are equally maximal, the last one is returned.
+    fn dyn_max(self) -> Option<Self::Item> {
+        self.max_by(|a, b| total_cmp(&**a, &**b))
+    }
+
+    /// Collects the elements of the iterator into a vector sorted by
+    /// [`sort_dyn`].
+    fn dyn_sorted(self) -> Vec<Self::Item> {
+        let mut values: Vec<_> = self.collect();
+        sort_dyn(&mut values);
+        values
+    }
+}
+
+impl<I: Iterator<Item = Box<dyn DynOrd>>> DynOrdIterExt for I {}
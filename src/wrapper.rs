@@ -0,0 +1,271 @@
+//! Wrapper types that adapt dynamically-typed values to the standard
+//! comparison traits.
+
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::borrow::Borrow;
+use core::ops::{Deref, DerefMut};
+use alloc::boxed::Box;
+use crate::{DynEq, DynOrd, DynTotalOrd, DynHash, total_cmp};
+
+/// A wrapper around a pointer to a `DynHash` value, providing `Eq` and
+/// `Hash` based on the pointee.
+///
+/// Equality and hashing are delegated to the `DynEq` and `DynHash`
+/// impls of the pointee, so an `EqBox<Box<dyn DynHash>>` hashes and
+/// compares exactly like the `dyn DynHash` value it points to,
+/// regardless of the kind of pointer it is contained in. In particular,
+/// `Eq` is only reflexive if the `PartialEq` impl of the underlying type
+/// is; see [`DynHash`].
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use dyn_ord::{DynHash, EqBox};
+/// let mut map: HashMap<EqBox<Box<dyn DynHash>>, i32> = HashMap::new();
+///
+/// map.insert(EqBox(Box::new(42)), 1);
+/// map.insert(EqBox(Box::new("qux")), 2);
+/// map.insert(EqBox(Box::new(42)), 3);
+///
+/// assert_eq!(map.len(), 2);
+/// assert_eq!(map[&EqBox(Box::new(42) as Box<dyn DynHash>)], 3);
+/// assert!(EqBox(&"qux") == EqBox(&"qux"));
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct EqBox<T>(pub T);
+
+impl<T> PartialEq for EqBox<T>
+where
+    T: Deref,
+    T::Target: DynHash,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0.dyn_eq(other.0.as_dyn_eq())
+    }
+}
+
+impl<T> Eq for EqBox<T>
+where
+    T: Deref,
+    T::Target: DynHash,
+{}
+
+impl<T> Hash for EqBox<T>
+where
+    T: Deref,
+    T::Target: DynHash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.dyn_hash(state)
+    }
+}
+
+/// Allows looking up boxed keys in hash-based collections by reference,
+/// without boxing the value being looked up. This is consistent with
+/// `Eq` and `Hash`, since both delegate to the pointee.
+impl<'a> Borrow<dyn DynHash + 'a> for EqBox<Box<dyn DynHash>> {
+    fn borrow(&self) -> &(dyn DynHash + 'a) {
+        &*self.0
+    }
+}
+
+impl<T> Deref for EqBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for EqBox<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// A wrapper around a pointer to a `DynOrd` value, providing a total
+/// order (`Ord`) based on the pointee.
+///
+/// Values of the same underlying type are compared using `DynOrd`.
+/// Values of different types are ordered by their `TypeId`, which is
+/// arbitrary, but consistent within a single run of the program. Values
+/// that are incomparable with themselves (e.g. floating-point NaNs) are
+/// ordered before the other values of the same type, and they are equal
+/// to each other. `PartialEq` and `PartialOrd` are consistent with `Ord`.
+///
+/// Other incomparable values of the same type are considered equal, so
+/// this is only a total order if every underlying type is totally ordered
+/// apart from self-incomparable values. This holds for `Ord` types and
+/// for floating-point numbers, which are therefore safe to use as keys
+/// in a `BTreeMap` or `BTreeSet`. Genuinely partially ordered types,
+/// such as sets ordered by inclusion, are not: a value could then be
+/// equal to two values that are not equal to each other, which breaks
+/// transitivity and can corrupt the collection.
+///
+/// ```
+/// # use std::collections::BTreeSet;
+/// # use dyn_ord::{DynOrd, OrdBox};
+/// let mut set: BTreeSet<OrdBox<Box<dyn DynOrd>>> = BTreeSet::new();
+///
+/// set.insert(OrdBox(Box::new(2.5)));
+/// set.insert(OrdBox(Box::new("qux")));
+/// set.insert(OrdBox(Box::new(-1.0)));
+/// set.insert(OrdBox(Box::new("baz")));
+/// set.insert(OrdBox(Box::new(2.5)));
+///
+/// assert_eq!(set.len(), 4);
+///
+/// let floats: Vec<f64> = set.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+/// let strs: Vec<&str> = set.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+///
+/// assert_eq!(floats, [-1.0, 2.5]);
+/// assert_eq!(strs, ["baz", "qux"]);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct OrdBox<T>(pub T);
+
+impl<T> PartialEq for OrdBox<T>
+where
+    T: Deref,
+    T::Target: DynOrd,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for OrdBox<T>
+where
+    T: Deref,
+    T::Target: DynOrd,
+{}
+
+impl<T> PartialOrd for OrdBox<T>
+where
+    T: Deref,
+    T::Target: DynOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for OrdBox<T>
+where
+    T: Deref,
+    T::Target: DynOrd,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        total_cmp(self.0.as_dyn_ord(), other.0.as_dyn_ord())
+    }
+}
+
+/// Allows looking up boxed keys in ordered collections by reference,
+/// without boxing the value being looked up. This is consistent with
+/// `Ord`, since both orders compare types first, then values.
+impl<'a> Borrow<dyn DynTotalOrd + 'a> for OrdBox<Box<dyn DynTotalOrd>> {
+    fn borrow(&self) -> &(dyn DynTotalOrd + 'a) {
+        &*self.0
+    }
+}
+
+impl<T> Deref for OrdBox<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for OrdBox<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// Compares two values, treating values that are incomparable with
+/// themselves (e.g. floating-point NaNs) as `nan_ordering` than all other
+/// values, and as equal to each other.
+fn nan_aware_cmp<T: PartialOrd>(lhs: &T, rhs: &T, nan_ordering: Ordering) -> Option<Ordering> {
+    let is_nan = |x: &T| x.partial_cmp(x).is_none();
+
+    match (is_nan(lhs), is_nan(rhs)) {
+        (true, true) => Some(Ordering::Equal),
+        (true, false) => Some(nan_ordering),
+        (false, true) => Some(nan_ordering.reverse()),
+        (false, false) => lhs.partial_cmp(rhs),
+    }
+}
+
+/// A wrapper that orders NaN before all other values of the same type.
+///
+/// A value is considered NaN if it is incomparable with itself. NaNs
+/// compare equal to each other, so wrapped floating-point numbers can be
+/// sorted as `dyn DynOrd` without any incomparable pairs.
+///
+/// ```
+/// # use dyn_ord::{DynOrd, NaNIsMin};
+/// let nan: Box<dyn DynOrd> = Box::new(NaNIsMin(f64::NAN));
+/// let one: Box<dyn DynOrd> = Box::new(NaNIsMin(1.0));
+/// let inf: Box<dyn DynOrd> = Box::new(NaNIsMin(f64::NEG_INFINITY));
+///
+/// assert!(*nan < *inf);
+/// assert!(*inf < *one);
+/// assert!(*nan == *nan);
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NaNIsMin<T: PartialOrd>(pub T);
+
+impl<T: PartialOrd> PartialEq for NaNIsMin<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for NaNIsMin<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        nan_aware_cmp(&self.0, &other.0, Ordering::Less)
+    }
+}
+
+/// A wrapper that orders NaN after all other values of the same type.
+///
+/// A value is considered NaN if it is incomparable with itself. NaNs
+/// compare equal to each other, so wrapped floating-point numbers can be
+/// sorted as `dyn DynOrd` without any incomparable pairs.
+///
+/// ```
+/// # use dyn_ord::{DynOrd, NaNIsMax};
+/// # use dyn_ord::util::sort_dyn;
+/// let mut values: Vec<Box<dyn DynOrd>> = vec![
+///     Box::new(NaNIsMax(f64::NAN)),
+///     Box::new(NaNIsMax(2.0)),
+///     Box::new(NaNIsMax(f64::INFINITY)),
+///     Box::new(NaNIsMax(1.0)),
+/// ];
+///
+/// sort_dyn(&mut values);
+///
+/// let floats: Vec<f64> = values
+///     .iter()
+///     .filter_map(|x| x.downcast_ref::<NaNIsMax<f64>>())
+///     .map(|x| x.0)
+///     .collect();
+///
+/// assert_eq!(floats[..3], [1.0, 2.0, f64::INFINITY]);
+/// assert!(floats[3].is_nan());
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct NaNIsMax<T: PartialOrd>(pub T);
+
+impl<T: PartialOrd> PartialEq for NaNIsMax<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for NaNIsMax<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        nan_aware_cmp(&self.0, &other.0, Ordering::Greater)
+    }
+}
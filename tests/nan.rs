@@ -0,0 +1,213 @@
+//! Regression tests for heterogeneous values that are incomparable with
+//! themselves, which used to make the order of `total_cmp` intransitive.
+
+use std::collections::BTreeSet;
+use dyn_ord::{DynEq, DynOrd, OrdBox};
+use dyn_ord::util::{sort_dyn, sort_dyn_unstable, dyn_select_nth};
+use dyn_ord::util::{dyn_binary_search, dyn_range, dyn_merge};
+use dyn_ord::util::{dyn_intersection, dyn_union, dyn_symmetric_diff};
+use dyn_ord::util::{dyn_count_distinct, dyn_count_distinct_sorted};
+use dyn_ord::util::{TypeNameOrdPolicy, TypeIdPolicy, sort_dyn_with_policy, sort_dyn_unstable_with_policy};
+
+/// Returns a pseudo-random sequence of `f64`s, about a fifth of which are
+/// NaN, interspersed with values of a different type.
+fn values_with_nan(len: usize, seed: u64) -> Vec<Box<dyn DynOrd>> {
+    let mut state = seed;
+    let mut next = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        state >> 33
+    };
+
+    (0..len)
+        .map(|_| {
+            let x = next();
+            match x % 10 {
+                0 | 1 => Box::new(f64::NAN) as Box<dyn DynOrd>,
+                2 => Box::new(x as i32 % 100),
+                _ => Box::new((x % 1000) as f64 / 10.0),
+            }
+        })
+        .collect()
+}
+
+/// Asserts that the values are grouped by type, that NaNs come first
+/// among the floats, and that the other values are in ascending order.
+fn assert_sorted(values: &[Box<dyn DynOrd>]) {
+    let floats: Vec<f64> = values.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+    let ints: Vec<i32> = values.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+    let nans = floats.iter().take_while(|x| x.is_nan()).count();
+
+    let type_changes = values.windows(2).filter(|w| w[0].is::<f64>() != w[1].is::<f64>()).count();
+    assert!(type_changes <= 1);
+
+    assert!(floats[nans..].iter().all(|x| !x.is_nan()));
+    assert!(floats[nans..].windows(2).all(|w| w[0] <= w[1]));
+    assert!(ints.windows(2).all(|w| w[0] <= w[1]));
+}
+
+/// Returns `true` if the values are sorted in the order of `sort_dyn`.
+fn is_sorted(values: &[&dyn DynOrd]) -> bool {
+    values.windows(2).all(|w| OrdBox(w[0]) <= OrdBox(w[1]))
+}
+
+type Values = Vec<Box<dyn DynOrd>>;
+
+/// Returns two sorted pseudo-random sequences containing NaNs.
+fn sorted_pair(seed: u64) -> (Values, Values) {
+    let mut a = values_with_nan(300 + seed as usize * 7, seed);
+    let mut b = values_with_nan(200 + seed as usize * 11, seed + 1000);
+
+    sort_dyn(&mut a);
+    sort_dyn(&mut b);
+
+    (a, b)
+}
+
+#[test]
+fn sort_dyn_with_nan() {
+    for (seed, len) in (0..50).zip((50..2000).step_by(40)) {
+        let mut values = values_with_nan(len, seed);
+        sort_dyn(&mut values);
+        assert_sorted(&values);
+    }
+}
+
+#[test]
+fn sort_dyn_unstable_with_nan() {
+    for (seed, len) in (0..50).zip((50..2000).step_by(40)) {
+        let mut values = values_with_nan(len, seed);
+        sort_dyn_unstable(&mut values);
+        assert_sorted(&values);
+    }
+}
+
+#[test]
+fn sort_dyn_with_policy_with_nan() {
+    for (seed, len) in (0..50).zip((50..2000).step_by(40)) {
+        let mut values = values_with_nan(len, seed);
+        sort_dyn_with_policy(&mut values, &TypeIdPolicy);
+        assert_sorted(&values);
+
+        let mut values = values_with_nan(len, seed);
+        sort_dyn_unstable_with_policy(&mut values, &TypeNameOrdPolicy);
+        assert_sorted(&values);
+    }
+}
+
+#[test]
+fn ord_box_set_with_nan() {
+    let mut set: BTreeSet<OrdBox<Box<dyn DynOrd>>> = BTreeSet::new();
+
+    for &x in &[3.0, f64::NAN, 1.0, 2.0, 5.0, 4.0, f64::NAN] {
+        set.insert(OrdBox(Box::new(x)));
+    }
+
+    let floats: Vec<f64> = set.iter().filter_map(|x| x.downcast_ref().copied()).collect();
+
+    assert_eq!(floats.len(), 6);
+    assert!(floats[0].is_nan());
+    assert_eq!(floats[1..], [1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert!(set.contains(&OrdBox(Box::new(f64::NAN) as Box<dyn DynOrd>)));
+    assert!(set.contains(&OrdBox(Box::new(4.0) as Box<dyn DynOrd>)));
+}
+
+#[test]
+fn dyn_select_nth_with_nan() {
+    for (seed, len) in (0..50).zip((50..2000).step_by(40)) {
+        let mut sorted = values_with_nan(len, seed);
+        sort_dyn(&mut sorted);
+
+        for k in [0, len / 7, len / 3, len / 2, len - 1] {
+            let mut values = values_with_nan(len, seed);
+            dyn_select_nth(&mut values, k);
+
+            let nth = OrdBox(&*values[k]);
+            assert!(nth == OrdBox(&*sorted[k]));
+            assert!(values[..k].iter().all(|x| OrdBox(&**x) <= nth));
+            assert!(values[k + 1..].iter().all(|x| OrdBox(&**x) >= nth));
+        }
+    }
+}
+
+#[test]
+fn dyn_binary_search_with_nan() {
+    for seed in 0..20 {
+        let (a, _) = sorted_pair(seed);
+
+        for x in &a {
+            let i = dyn_binary_search(&a, &**x).unwrap();
+            assert!(OrdBox(&*a[i]) == OrdBox(&**x));
+            assert!(i == 0 || OrdBox(&*a[i - 1]) < OrdBox(&**x));
+        }
+
+        assert!(dyn_binary_search(&a, &0.05).is_err());
+    }
+}
+
+#[test]
+fn dyn_range_with_nan() {
+    for seed in 0..20 {
+        let (a, _) = sorted_pair(seed);
+
+        let range: Vec<f64> = dyn_range(&a, &10.0, &50.0)
+            .iter()
+            .map(|x| *x.downcast_ref::<f64>().unwrap())
+            .collect();
+        let expected: Vec<f64> = a
+            .iter()
+            .filter_map(|x| x.downcast_ref::<f64>().copied())
+            .filter(|x| (10.0..=50.0).contains(x))
+            .collect();
+        assert_eq!(range, expected);
+
+        let with_nan = dyn_range(&a, &f64::NAN, &50.0);
+        let nans = a.iter().filter(|x| x.downcast_ref::<f64>().is_some_and(|x| x.is_nan())).count();
+        let up_to_50 = a.iter().filter(|x| x.downcast_ref::<f64>().is_some_and(|x| *x <= 50.0)).count();
+        assert_eq!(with_nan.len(), nans + up_to_50);
+    }
+}
+
+#[test]
+fn dyn_set_operations_with_nan() {
+    for seed in 0..20 {
+        let (a, b) = sorted_pair(seed);
+
+        let merged = dyn_merge(&a, &b);
+        let common = dyn_intersection(&a, &b);
+        let all = dyn_union(&a, &b);
+        let diff = dyn_symmetric_diff(&a, &b);
+
+        assert!(is_sorted(&merged));
+        assert!(is_sorted(&common));
+        assert!(is_sorted(&all));
+        assert!(is_sorted(&diff));
+
+        // NaNs are never equal, so they are never common to both slices
+        let expected_common = a
+            .iter()
+            .enumerate()
+            .filter(|&(i, x)| {
+                let in_a = a[..=i].iter().filter(|y| ***y == **x).count();
+                let in_b = b.iter().filter(|y| ***y == **x).count();
+                (1..=in_b).contains(&in_a)
+            })
+            .count();
+
+        assert_eq!(merged.len(), a.len() + b.len());
+        assert_eq!(common.len(), expected_common);
+        assert_eq!(all.len(), a.len() + b.len() - common.len());
+        assert_eq!(diff.len(), a.len() + b.len() - 2 * common.len());
+        assert!(common.iter().all(|x| **x == **x));
+    }
+}
+
+#[test]
+fn dyn_count_distinct_sorted_with_nan() {
+    for seed in 0..20 {
+        let (a, _) = sorted_pair(seed);
+        let distinct = dyn_count_distinct_sorted(&a);
+        let a: Vec<Box<dyn DynEq>> = a.into_iter().map(|x| x as Box<dyn DynEq>).collect();
+
+        assert_eq!(distinct, dyn_count_distinct(&a));
+    }
+}
@@ -0,0 +1,332 @@
+//! Tests confirming which standard types are covered by the blanket impls
+//! of `DynEq` and `DynOrd`, and how they compare.
+
+// comparing trait objects with owned values of the concrete type is the
+// point of these tests
+#![allow(clippy::cmp_owned)]
+
+use core::cell::{Cell, RefCell};
+use core::cmp::{Ordering, Reverse};
+use core::num::Wrapping;
+use core::ops::Range;
+use core::ptr::NonNull;
+use core::sync::atomic::{self, AtomicI32};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use dyn_ord::{DynEq, DynOrd, DynEqDisplay, boxed_display};
+
+#[test]
+fn static_trait_objects() {
+    fn eq(a: &(dyn DynEq + 'static), b: &(dyn DynEq + 'static)) -> bool {
+        *a == *b
+    }
+
+    fn lt(a: &(dyn DynOrd + Send + 'static), b: &(dyn DynOrd + Send + 'static)) -> bool {
+        *a < *b
+    }
+
+    assert!(eq(&42, &42));
+    assert!(lt(&1, &2));
+}
+
+#[test]
+fn nested_trait_objects() {
+    let inner: Box<dyn DynEq> = Box::new(42);
+    let outer: Box<dyn DynEq> = Box::new(inner);
+
+    assert!(outer.is::<Box<dyn DynEq>>());
+    assert!(*outer == *(Box::new(Box::new(42) as Box<dyn DynEq>) as Box<dyn DynEq>));
+    assert!(*outer != *(Box::new(Box::new('x') as Box<dyn DynEq>) as Box<dyn DynEq>));
+    assert!(*outer != 42);
+}
+
+#[test]
+fn pin() {
+    let x: Box<dyn DynEq> = Box::new(Box::pin(42));
+
+    assert!(*x == Box::pin(42));
+    assert!(*x != 42);
+    assert!(x.is::<Pin<Box<i32>>>());
+
+    let x: Box<dyn DynOrd> = Box::new(Box::pin(1));
+    let y: Box<dyn DynOrd> = Box::new(Box::pin(2));
+
+    assert!(*x < *y);
+    assert!(*x < Box::pin(2));
+    assert_eq!((*x).partial_cmp(&1), None);
+}
+
+#[test]
+fn non_null() {
+    let mut value = 42_i32;
+    let ptr = NonNull::from(&mut value);
+
+    let x: &dyn DynEq = &ptr;
+
+    assert!(*x == ptr);
+    assert!(*x != NonNull::from(&mut 42_i32));
+    assert!(*x != ptr.cast::<u32>());
+}
+
+#[test]
+fn wrapping() {
+    let x: &dyn DynEq = &Wrapping(42_u32);
+
+    assert!(*x == Wrapping(42_u32));
+    assert!(*x != Wrapping(43_u32));
+    assert!(*x != 42_u32);
+}
+
+#[test]
+fn reverse() {
+    let x: &dyn DynOrd = &Reverse(1);
+    let y: &dyn DynOrd = &Reverse(2);
+
+    assert!(*x > *y);
+    assert!(*x == Reverse(1));
+    assert_eq!(x.partial_cmp(&1), None);
+
+    let z: &dyn DynEq = &Reverse(1);
+    assert!(*z == Reverse(1));
+    assert!(*z != 1);
+}
+
+#[test]
+fn ip_and_socket_addr() {
+    let v4 = IpAddr::V4(Ipv4Addr::LOCALHOST);
+    let x: &dyn DynEq = &v4;
+
+    assert!(*x == IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+    assert!(*x != IpAddr::V6(Ipv6Addr::LOCALHOST));
+    assert!(*x != Ipv4Addr::LOCALHOST);
+
+    let y: &dyn DynEq = &SocketAddr::new(v4, 8080);
+
+    assert!(*y == SocketAddr::new(v4, 8080));
+    assert!(*y != SocketAddr::new(v4, 8081));
+    assert!(*y != v4);
+}
+
+#[test]
+fn btree_collections() {
+    let x: &dyn DynEq = &BTreeSet::from([1, 2, 3]);
+    let y: &dyn DynEq = &BTreeSet::<String>::new();
+
+    assert!(*x == *x);
+    assert!(*x == BTreeSet::from([3, 2, 1]));
+    assert!(*x != BTreeSet::from([1, 2]));
+    assert!(*y != BTreeSet::<i32>::new());
+    assert!(*x != *y);
+
+    let z: &dyn DynEq = &BTreeMap::from([("qux", 1)]);
+
+    assert!(*z == BTreeMap::from([("qux", 1)]));
+    assert!(*z != BTreeMap::from([("qux", 2)]));
+    assert!(*z != BTreeMap::from([("qux", 1_u8)]));
+}
+
+#[test]
+fn duration_and_instant() {
+    let x: &dyn DynOrd = &Duration::from_secs(1);
+    let y: &dyn DynOrd = &Duration::from_millis(1500);
+
+    assert!(*x < *y);
+    assert!(*x == Duration::from_millis(1000));
+
+    let start = Instant::now();
+    let z: &dyn DynOrd = &start;
+
+    assert!(*z <= Instant::now());
+    assert_eq!(x.partial_cmp(z), None);
+}
+
+#[test]
+fn range() {
+    let x: &dyn DynEq = &(1..5);
+    assert!(*x == (1..5));
+    assert!(*x != (1..6));
+
+    // `Range` is not `PartialOrd`, but its bounds are
+    let bounds = |r: Range<i32>| (r.start, r.end);
+    let y: &dyn DynOrd = &bounds(1..5);
+    let z: &dyn DynOrd = &bounds(2..3);
+
+    assert!(*y < *z);
+    assert!(*y == (1, 5));
+}
+
+#[test]
+fn cell() {
+    let cell = Cell::new(42);
+    let x: &dyn DynEq = &cell;
+
+    assert!(*x == *x);
+    assert!(*x == Cell::new(42));
+
+    cell.set(43);
+    assert!(*x != Cell::new(42));
+    assert!(*x != 43);
+}
+
+#[test]
+fn ref_cell() {
+    let cell = RefCell::new(vec![1, 2]);
+    let x: &dyn DynEq = &cell;
+
+    cell.borrow_mut().push(3);
+    assert!(*x == RefCell::new(vec![1, 2, 3]));
+}
+
+#[test]
+#[should_panic]
+fn mutably_borrowed_ref_cell() {
+    let cell = RefCell::new(42);
+    let x: &dyn DynEq = &cell;
+    let _guard = cell.borrow_mut();
+
+    // panics because the cell is already mutably borrowed
+    let _ = *x == *x;
+}
+
+#[test]
+fn mutex_newtype() {
+    struct Shared<T>(Mutex<T>);
+
+    impl<T: PartialEq> PartialEq for Shared<T> {
+        fn eq(&self, other: &Self) -> bool {
+            // locking the same mutex twice may deadlock or panic, and
+            // distinct mutexes must be locked in a consistent order
+            if core::ptr::eq(self, other) {
+                return true;
+            }
+            let (first, second) = if (self as *const Self) < other {
+                (self, other)
+            } else {
+                (other, self)
+            };
+            match (first.0.lock(), second.0.lock()) {
+                (Ok(lhs), Ok(rhs)) => *lhs == *rhs,
+                _ => false, // poisoned
+            }
+        }
+    }
+
+    let x: &dyn DynEq = &Shared(Mutex::new(42));
+
+    assert!(*x == *x);
+    assert!(*x == Shared(Mutex::new(42)));
+    assert!(*x != Shared(Mutex::new(43)));
+}
+
+#[test]
+fn atomic_newtype() {
+    struct Counter(AtomicI32);
+
+    impl PartialEq for Counter {
+        fn eq(&self, other: &Self) -> bool {
+            self.0.load(atomic::Ordering::Relaxed) == other.0.load(atomic::Ordering::Relaxed)
+        }
+    }
+
+    let counter = Counter(AtomicI32::new(0));
+    let x: &dyn DynEq = &counter;
+
+    counter.0.fetch_add(1, atomic::Ordering::Relaxed);
+    assert!(*x == Counter(AtomicI32::new(1)));
+}
+
+#[test]
+fn display() {
+    let x: Box<dyn DynEqDisplay> = boxed_display(Wrapping(42));
+    let y: Box<dyn DynEqDisplay> = boxed_display(Ipv4Addr::LOCALHOST);
+
+    assert_eq!(x.to_string(), "42");
+    assert_eq!(y.to_string(), "127.0.0.1");
+    assert!(*x != *y);
+    assert!(*x == *boxed_display(Wrapping(42)));
+    assert!(*x != *boxed_display(42));
+}
+
+#[test]
+fn slices() {
+    let boxed: Box<dyn DynEq> = Box::new(Box::<[i32]>::from([1, 2, 3]));
+    let other: Box<dyn DynEq> = Box::new(vec![1, 2, 3].into_boxed_slice());
+    let borrowed: &dyn DynEq = &&[1, 2, 3][..];
+
+    assert!(*boxed == *other);
+    assert!(*boxed != *borrowed);
+    assert!(*borrowed == *(&&[1, 2, 3][..] as &dyn DynEq));
+
+    let x: Box<dyn DynOrd> = Box::new(Box::<[i32]>::from([1, 2, 3]));
+    let y: Box<dyn DynOrd> = Box::new(Box::<[i32]>::from([1, 3]));
+    let z: &dyn DynOrd = &&[1, 2][..];
+
+    assert!(*x < *y);
+    assert!(*z == *(&&[1, 2][..] as &dyn DynOrd));
+    assert!(*z < *(&&[1, 2, 0][..] as &dyn DynOrd));
+    assert_eq!((*x).partial_cmp(z), None);
+}
+
+#[test]
+fn strings() {
+    let literal: &dyn DynOrd = &"qux";
+    let boxed: Box<dyn DynOrd> = Box::new(Box::<str>::from("qux"));
+    let string: Box<dyn DynOrd> = Box::new(String::from("qux"));
+
+    assert!(*literal == *(&"qux" as &dyn DynOrd));
+    assert!(*literal < *(&"quz" as &dyn DynOrd));
+    assert!(*literal != *boxed);
+    assert!(*boxed != *string);
+}
+
+#[test]
+fn cow() {
+    let x: &dyn DynEq = &Cow::<str>::Borrowed("qux");
+
+    assert!(*x == Cow::<str>::Owned(String::from("qux")));
+    assert!(*x != Cow::<str>::Borrowed("baz"));
+    assert!(*x != "qux");
+}
+
+#[test]
+fn paths() {
+    let borrowed: &dyn DynEq = &Path::new("/tmp");
+    let owned: &dyn DynEq = &PathBuf::from("/tmp");
+
+    assert!(*borrowed == *(&Path::new("/tmp/") as &dyn DynEq));
+    assert!(*borrowed != *(&Path::new("/var") as &dyn DynEq));
+    assert!(*owned == PathBuf::from("/tmp"));
+    assert!(*owned != *borrowed);
+}
+
+#[test]
+fn dyn_ord_implies_dyn_eq() {
+    fn compare(a: &dyn DynOrd, b: &dyn DynOrd) -> (Option<bool>, Option<Ordering>) {
+        (a.dyn_eq_typed(b), a.partial_cmp(b))
+    }
+
+    assert_eq!(compare(&1, &2), (Some(false), Some(Ordering::Less)));
+    assert_eq!(compare(&1, &1), (Some(true), Some(Ordering::Equal)));
+    assert_eq!(compare(&1, &"qux"), (None, None));
+}
+
+#[test]
+fn send_sync_dyn_ord() {
+    let values: Arc<Mutex<Vec<Box<dyn DynOrd + Send + Sync>>>> = Arc::new(Mutex::new(vec![
+        Box::new(3),
+        Box::new(1),
+        Box::new(2),
+    ]));
+
+    let mut values = values.lock().unwrap();
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    assert!(*values[0] < *values[1]);
+    assert!(*values[1] < *values[2]);
+    assert!(*values[2] == *values[2]);
+}